@@ -15,15 +15,17 @@ fn main() {
         string(),
         true,
         2 + 2,
-        12.3
+        12.3,
+        b"\x01\x02"
     );
-    assert_eq!(a.len(), 8);
+    assert_eq!(a.len(), 9);
     assert!(matches!(&a[0], Arg::Pos(Value::String(val)) if val == "a"));
-    assert!(matches!(&a[1], Arg::Pos(Value::String(val)) if val == "b"));
+    assert!(matches!(&a[1], Arg::Pos(Value::Char('b'))));
     assert!(matches!(&a[2], Arg::Pos(Value::Int64(3))));
     assert!(matches!(&a[3], Arg::Pos(Value::String(val)) if val == "owned"));
     assert!(matches!(&a[4], Arg::Pos(Value::String(val)) if val == "fun"));
     assert!(matches!(&a[5], Arg::Pos(Value::Bool(true))));
     assert!(matches!(&a[6], Arg::Pos(Value::Int64(4))));
     assert!(matches!(&a[7], Arg::Pos(Value::Float64(val)) if val.eq(&12.3)));
+    assert!(matches!(&a[8], Arg::Pos(Value::Bytes(val)) if val[..] == [1, 2]));
 }
@@ -3,18 +3,18 @@ use fmtlib::fmt::{Arg, Value};
 
 fn main() {
     // Args
-    let a = rt_format_args!("a": "v", 'b': "v", c: "v", 10: "v");
+    let a = rt_format_args!("a": "v", 'b': "v", c: "v", "_d": "v");
     assert_eq!(a.len(), 4);
     assert!(
-        matches!(&a[0], Arg::Named(k, Value::String(v)) if k.as_c_str() == unsafe{std::ffi::CStr::from_bytes_with_nul_unchecked(b"a\0")} && v == "v")
+        matches!(&a[0], Arg::Named(k, Value::String(v)) if k == "a" && v == "v")
     );
     assert!(
-        matches!(&a[1], Arg::Named(k, Value::String(v)) if k.as_c_str() == unsafe{std::ffi::CStr::from_bytes_with_nul_unchecked(b"b\0")} && v == "v")
+        matches!(&a[1], Arg::Named(k, Value::String(v)) if k == "b" && v == "v")
     );
     assert!(
-        matches!(&a[2], Arg::Named(k, Value::String(v)) if k.as_c_str() == unsafe{std::ffi::CStr::from_bytes_with_nul_unchecked(b"c\0")} && v == "v")
+        matches!(&a[2], Arg::Named(k, Value::String(v)) if k == "c" && v == "v")
     );
     assert!(
-        matches!(&a[3], Arg::Named(k, Value::String(v)) if k.as_c_str() == unsafe{std::ffi::CStr::from_bytes_with_nul_unchecked(b"10\0")} && v == "v")
+        matches!(&a[3], Arg::Named(k, Value::String(v)) if k == "_d" && v == "v")
     );
 }
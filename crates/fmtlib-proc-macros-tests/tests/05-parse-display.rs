@@ -0,0 +1,20 @@
+use fmtlib_proc_macros::rt_format_args;
+use fmtlib::fmt::{Arg, Value};
+
+#[derive(Debug)]
+struct Point(i32, i32);
+
+fn main() {
+    let addr = std::net::Ipv4Addr::LOCALHOST;
+    let p = Point(1, 2);
+    let a = rt_format_args!(%addr, ?p, %1 + 1, ?Some("x"), ip: %addr, point: ?p);
+    assert_eq!(a.len(), 6);
+    assert!(matches!(&a[0], Arg::Pos(Value::Display(v)) if v.to_string() == "127.0.0.1"));
+    assert!(matches!(&a[1], Arg::Pos(Value::Debug(v)) if format!("{v:?}") == "Point(1, 2)"));
+    assert!(matches!(&a[2], Arg::Pos(Value::Display(v)) if v.to_string() == "2"));
+    assert!(matches!(&a[3], Arg::Pos(Value::Debug(v)) if format!("{v:?}") == "Some(\"x\")"));
+    assert!(matches!(&a[4], Arg::Named(_, Value::Display(_))));
+    assert!(matches!(&a[5], Arg::Named(_, Value::Debug(_))));
+    let a = rt_format_args!(%p.0, ?p.1);
+    assert!(matches!(&a[1], Arg::Pos(Value::Debug(v)) if format!("{v:?}") == "2"));
+}
@@ -0,0 +1,8 @@
+use fmtlib_proc_macros::rt_format_args;
+use fmtlib::fmt::Arg;
+
+fn main() {
+    let _ = rt_format_args!(10: "v");
+    let _ = rt_format_args!("a-b": "v");
+    let _ = rt_format_args!('1': "v");
+}
@@ -5,5 +5,7 @@ fn tests() {
     t.pass("tests/02-parse-named.rs");
     t.pass("tests/03-parse-mixed.rs");
     t.pass("tests/04-parse-empty.rs");
+    t.pass("tests/05-parse-display.rs");
+    t.compile_fail("tests/fail/parse-invalid-key.rs");
     //t.compile_fail("tests/failing.rs");
 }
@@ -5,14 +5,37 @@ use syn::{
 };
 
 enum ArgMacroInput {
-    Pos(Expr),
-    Named(Expr, Expr),
+    Pos(ArgValue),
+    Named(Box<Expr>, ArgValue),
+}
+
+/// A value, optionally prefixed with `%` to format it through its `Display`
+/// impl or with `?` to format it through its `Debug` impl.
+enum ArgValue {
+    Value(Expr),
+    Display(Expr),
+    Debug(Expr),
+}
+
+impl Parse for ArgValue {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![%]) {
+            let _: Token![%] = input.parse()?;
+            Ok(Self::Display(input.parse()?))
+        } else if input.peek(Token![?]) {
+            let _: Token![?] = input.parse()?;
+            Ok(Self::Debug(input.parse()?))
+        } else {
+            Ok(Self::Value(input.parse()?))
+        }
+    }
 }
 
 impl ArgMacroInput {
     /// Parses either an expression matching V, or an expression matching K: V
     /// where V is an expression that evaluates to a value and K is an identifier
-    /// or an expression that evaluates to an identifier.
+    /// or an expression that evaluates to an identifier. V can be prefixed with
+    /// `%` or `?`, see [`ArgValue`].
     fn parse_alternate(input: syn::parse::ParseStream) -> syn::Result<Self> {
         // Has ':' not followed by ':'
         let res = if input.peek2(Token![:]) && !input.peek3(Token![:]) {
@@ -21,11 +44,11 @@ impl ArgMacroInput {
 
             // Ignore ':' token
             let _: Token![:] = input.parse()?;
-            let value: Expr = input.parse()?;
+            let value: ArgValue = input.parse()?;
 
-            Self::Named(key, value)
+            Self::Named(Box::new(key), value)
         } else {
-            let value: Expr = input.parse()?;
+            let value: ArgValue = input.parse()?;
             Self::Pos(value)
         };
 
@@ -38,12 +61,12 @@ impl ToTokens for ArgMacroInput {
         let ty = quote! {Arg};
         match self {
             ArgMacroInput::Pos(value) => {
-                let arg = expand_value(value);
+                let arg = expand_arg_value(value);
                 tokens.extend(quote! {#ty::positional(#arg)});
             }
             ArgMacroInput::Named(key, value) => {
                 let k = expand_key(key);
-                let v = expand_value(value);
+                let v = expand_arg_value(value);
                 tokens.extend(quote! {#ty::named(#k, #v)})
             }
         }
@@ -59,15 +82,25 @@ fn expand_key(key: &Expr) -> proc_macro2::TokenStream {
             };
         }
     } else if let Expr::Lit(expr) = key {
-        // Named argument keys must be C strings
+        // Named argument keys must be identifiers, as fmt parses them
         let expr = &expr.lit;
-        if let Lit::Int(v) = expr {
-            let val = v.base10_digits();
+        if let Lit::Str(v) = expr {
+            if !is_arg_name(&v.value()) {
+                return syn::Error::new(v.span(), "keys must be identifiers").to_compile_error();
+            }
+        } else if let Lit::Char(v) = expr {
+            let val = v.value().to_string();
+            if !is_arg_name(&val) {
+                return syn::Error::new(v.span(), "keys must be identifiers").to_compile_error();
+            }
             return quote_spanned! { v.span() =>
                 #val
             };
+        } else if let Lit::Int(v) = expr {
+            return syn::Error::new(v.span(), "integer literals are not supported as keys")
+                .to_compile_error();
         } else if let Lit::Float(v) = expr {
-            return syn::Error::new(v.span(), "bool literals are not supported as keys")
+            return syn::Error::new(v.span(), "float literals are not supported as keys")
                 .to_compile_error();
         } else if let Lit::Bool(v) = expr {
             return syn::Error::new(v.span(), "bool literals are not supported as keys")
@@ -78,11 +111,31 @@ fn expand_key(key: &Expr) -> proc_macro2::TokenStream {
     expand_value(key)
 }
 
+/// Whether `name` can be referenced from a format string, see
+/// `Arg::validate_name`.
+fn is_arg_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn expand_arg_value(value: &ArgValue) -> proc_macro2::TokenStream {
+    match value {
+        ArgValue::Value(v) => expand_value(v),
+        ArgValue::Display(v) => quote_spanned! {v.span() =>
+            &(#v) as &(dyn ::std::fmt::Display + Sync)
+        },
+        ArgValue::Debug(v) => quote_spanned! {v.span() =>
+            &(#v) as &(dyn ::std::fmt::Debug + Sync)
+        },
+    }
+}
+
 fn expand_value(value: &Expr) -> proc_macro2::TokenStream {
     match value {
-        Expr::Array(v) => {
-            syn::Error::new(v.span(), "array expressions are not supported").to_compile_error()
-        }
+        Expr::Array(v) => quote_spanned! {v.span() => #v},
         Expr::Assign(v) => {
             syn::Error::new(v.span(), "assignment expressions are not supported").to_compile_error()
         }
@@ -130,8 +183,7 @@ fn expand_value(value: &Expr) -> proc_macro2::TokenStream {
             syn::Error::new(v.span(), "range expressions are not supported").to_compile_error()
         }
         Expr::Reference(v) => quote_spanned! {v.span() => #v},
-        Expr::Repeat(v) => syn::Error::new(v.span(), "array literal expressions are not supported")
-            .to_compile_error(),
+        Expr::Repeat(v) => quote_spanned! {v.span() => #v},
         Expr::Return(v) => {
             syn::Error::new(v.span(), "return expressions are not supported").to_compile_error()
         }
@@ -142,8 +194,8 @@ fn expand_value(value: &Expr) -> proc_macro2::TokenStream {
         Expr::TryBlock(v) => {
             syn::Error::new(v.span(), "try block expressions are not supported").to_compile_error()
         }
-        Expr::Tuple(v) => quote_spanned! {v.span() => #v.into()},
-        Expr::Unary(v) => quote_spanned! {v.span() => #v.into()},
+        Expr::Tuple(v) => quote_spanned! {v.span() => #v},
+        Expr::Unary(v) => quote_spanned! {v.span() => #v},
         Expr::Unsafe(v) => quote_spanned! {v.span() => #v},
         Expr::Verbatim(v) => {
             syn::Error::new(v.span(), "verbatim expressions are not supported").to_compile_error()
@@ -162,16 +214,13 @@ fn expand_literal(literal: &Lit) -> proc_macro2::TokenStream {
     match literal {
         Lit::Str(v) => quote_spanned! {v.span() => #v},
         Lit::ByteStr(v) => {
-            syn::Error::new(v.span(), "byte string literals are not supported").to_compile_error()
+            // Byte slices otherwise convert into a sequence of integers.
+            quote_spanned! {v.span() => ::std::borrow::Cow::<[u8]>::Borrowed(#v)}
         }
         Lit::Byte(v) => {
             syn::Error::new(v.span(), "byte literals are not supported").to_compile_error()
         }
-        Lit::Char(v) => {
-            // Rust chars aren't compatoble with C chars. convert to a string.
-            let val = v.value().to_string();
-            quote_spanned! {v.span() => #val}
-        }
+        Lit::Char(v) => quote_spanned! {v.span() => #v},
         Lit::Int(v) => quote_spanned! {v.span() => #v},
         Lit::Float(v) => quote_spanned! {v.span() => #v},
         Lit::Bool(v) => quote_spanned! {v.span() => #v},
@@ -1,37 +1,762 @@
 #ifndef FFI_SHIM_INCLUDED
 #define FFI_SHIM_INCLUDED
+#include <algorithm>
+#include <cerrno>
+#include <cstdint>
+#include <cstdio>
 #include <fmt/args.h>
+#include <fmt/chrono.h>
+#include <fmt/color.h>
 #include <fmt/core.h>
+#include <fmt/format.h>
+#include <fmt/ranges.h>
+#include <iterator>
+#include <locale>
 #include <memory>
+#include <string>
+#include <system_error>
+#include <vector>
 #include "fmtlib/src/ffi.rs.h"
 #include "rust/cxx.h"
 #include <iostream>
 
 namespace shimcpp {
+    // A unicode scalar value that doesn't fit in a C++ char.
+    struct code_point {
+        char32_t value;
+    };
+
+    // A value whose type is only known when it's formatted, e.g. an element
+    // of a `Value::Seq`.
+    struct value_ref {
+        const shimrs::Value *value;
+    };
+
+    struct seq_ref {
+        const shimrs::Value *value;
+    };
+
+    struct map_ref {
+        const shimrs::Value *value;
+    };
+
+    struct tuple_ref {
+        const shimrs::Value *value;
+    };
+
+    struct bytes_ref {
+        const shimrs::Value *value;
+    };
+
+    struct datetime_ref {
+        const shimrs::Value *value;
+    };
+
+    // A `Value::Decimal`, formatted by Rust with the format call's locale.
+    struct decimal_ref {
+        const shimrs::Value *value;
+    };
+
+    // A `Value::Styled`, rendered through fmt's `styled` unless colors are
+    // disabled.
+    struct styled_ref {
+        const shimrs::Value *value;
+    };
+
+    // Mirrors `fmtlib::fmt::Color`'s encoding.
+    constexpr std::uint32_t rgb_flag = 1u << 24;
+
+    inline ::fmt::text_style to_text_style(const shimrs::Style &style) {
+        ::fmt::text_style ts(static_cast<::fmt::emphasis>(style.get_emphasis()));
+        auto fg = style.get_fg(), bg = style.get_bg();
+        if (fg & rgb_flag) {
+            ts |= ::fmt::fg(::fmt::rgb(fg & ~rgb_flag));
+        } else if (fg != 0) {
+            ts |= ::fmt::fg(static_cast<::fmt::terminal_color>(fg));
+        }
+        if (bg & rgb_flag) {
+            ts |= ::fmt::bg(::fmt::rgb(bg & ~rgb_flag));
+        } else if (bg != 0) {
+            ts |= ::fmt::bg(static_cast<::fmt::terminal_color>(bg));
+        }
+        return ts;
+    }
+
+    // A `Value::Custom`, formatted by its Rust `Format` implementation.
+    struct custom_ref {
+        const shimrs::Value *value;
+    };
+
+    // A `Value::Display` or `Value::Debug`, rendered by Rust when it's
+    // formatted and then formatted as a string.
+    struct display_ref {
+        const shimrs::Value *value;
+    };
+
+    struct debug_ref {
+        const shimrs::Value *value;
+    };
+
+    template<typename T>
+    auto set_tm_zone(T &tm, long offset, const char *zone, int) -> decltype(tm.tm_gmtoff, tm.tm_zone, void()) {
+        tm.tm_gmtoff = offset;
+        tm.tm_zone = const_cast<decltype(tm.tm_zone)>(zone);
+    }
+
+    // Without these fields fmt falls back to the host's time zone for `%z`
+    // and `%Z`.
+    template<typename T>
+    void set_tm_zone(T &, long, const char *, ...) {}
+
+    // The text of a `Value::None`. Unlike strings, it isn't quoted inside
+    // ranges and optionals.
+    struct none_text {
+        ::fmt::string_view text;
+    };
+
+    // A `Value::Some` rendered in fmt's `std::optional` style.
+    struct optional_ref {
+        const shimrs::Value *value;
+    };
+
+    // Mirrors `fmtlib::fmt::NoneStyle`.
+    enum class none_style : std::uint8_t {
+        empty = 0,
+        none = 1,
+        optional = 2,
+    };
+
+    inline std::size_t encode_utf8(char32_t c, char *out) {
+        if (c < 0x80) {
+            out[0] = static_cast<char>(c);
+            return 1;
+        } else if (c < 0x800) {
+            out[0] = static_cast<char>(0xC0 | (c >> 6));
+            out[1] = static_cast<char>(0x80 | (c & 0x3F));
+            return 2;
+        } else if (c < 0x10000) {
+            out[0] = static_cast<char>(0xE0 | (c >> 12));
+            out[1] = static_cast<char>(0x80 | ((c >> 6) & 0x3F));
+            out[2] = static_cast<char>(0x80 | (c & 0x3F));
+            return 3;
+        }
+        out[0] = static_cast<char>(0xF0 | (c >> 18));
+        out[1] = static_cast<char>(0x80 | ((c >> 12) & 0x3F));
+        out[2] = static_cast<char>(0x80 | ((c >> 6) & 0x3F));
+        out[3] = static_cast<char>(0x80 | (c & 0x3F));
+        return 4;
+    }
+
+    // A `fmtlib::fmt::Locale`'s punctuation. `std::numpunct<char>` only has
+    // single byte separators, so non-ASCII ones are kept as UTF-8 for the
+    // formatters that support them.
+    struct numpunct : std::numpunct<char> {
+        std::string grouping_;
+        std::string thousands_sep_;
+        std::string decimal_point_;
+
+        explicit numpunct(const shimrs::Locale &locale) {
+            auto grouping = locale.get_grouping();
+            grouping_.assign(reinterpret_cast<const char*>(grouping.data()), grouping.size());
+            char buf[4];
+            thousands_sep_.assign(buf, encode_utf8(static_cast<char32_t>(locale.get_thousands_sep()), buf));
+            decimal_point_.assign(buf, encode_utf8(static_cast<char32_t>(locale.get_decimal_point()), buf));
+        }
+
+    protected:
+        char do_thousands_sep() const override {
+            return thousands_sep_.size() == 1 ? thousands_sep_[0] : ',';
+        }
+
+        std::string do_grouping() const override {
+            return thousands_sep_.size() == 1 ? grouping_ : std::string();
+        }
+
+        char do_decimal_point() const override {
+            return decimal_point_.size() == 1 ? decimal_point_[0] : '.';
+        }
+    };
+
+    // Integers are grouped through fmt's `format_facet` where it's available,
+    // which takes UTF-8 separators.
+    inline std::locale make_locale(const shimrs::Locale &locale) {
+        auto punct = new numpunct(locale);
+        std::locale loc(std::locale::classic(), punct);
+#if FMT_VERSION >= 100000
+        // `format_facet` expects a non-empty grouping unless the separator is
+        // empty.
+        auto sep = punct->grouping_.empty() ? std::string() : punct->thousands_sep_;
+        loc = std::locale(loc, new ::fmt::format_facet<std::locale>(sep, punct->grouping_, punct->decimal_point_));
+#endif
+        return loc;
+    }
+
+    // Captures the spec of the replacement field being parsed into `spec`,
+    // without the closing brace, and returns its end. Used by formatters that
+    // only know how to interpret the spec once they see the value.
+    //
+    // The captured spec is parsed again in a context of its own, so nested
+    // fields are resolved against `ctx` here: `{}` is given the next argument
+    // id and explicit ids are checked, as when fmt parses them.
+    inline auto capture_spec(::fmt::format_parse_context &ctx, std::string &spec) -> const char* {
+        auto it = ctx.begin(), end = ctx.end();
+        spec.clear();
+        while (it != end && *it != '}') {
+            if (*it != '{') {
+                spec.push_back(*it++);
+                continue;
+            }
+            auto id_begin = ++it;
+            while (it != end && *it != '}') ++it;
+            // fmt reports the missing brace.
+            if (it == end) break;
+            ::fmt::string_view id(id_begin, static_cast<std::size_t>(it - id_begin));
+            ++it;
+            if (id.size() == 0) {
+                spec += "{" + std::to_string(ctx.next_arg_id()) + "}";
+                continue;
+            }
+            if (std::all_of(id.begin(), id.end(), [](char c) { return c >= '0' && c <= '9'; })) {
+                ctx.check_arg_id(std::stoi(std::string(id.data(), id.size())));
+            } else {
+                ctx.check_arg_id(id);
+            }
+            spec.push_back('{');
+            spec.append(id.data(), id.size());
+            spec.push_back('}');
+        }
+        return it;
+    }
+
+    template<typename F>
+    auto set_debug_format(F &f, int) -> decltype(f.set_debug_format(true)) {
+        f.set_debug_format(true);
+    }
+
+    template<typename F>
+    void set_debug_format(F &, ...) {}
+
+    // Returns the size of the fill, alignment and width at the start of a
+    // spec captured by `capture_spec`.
+    inline auto padding_size(const std::string &spec) -> std::size_t {
+        auto is_align = [](char c) { return c == '<' || c == '>' || c == '^'; };
+        std::size_t size = 0;
+        if (!spec.empty()) {
+            // The fill is a single, possibly multibyte, code point.
+            auto lead = static_cast<unsigned char>(spec[0]);
+            std::size_t fill = lead >= 0xF0 ? 4 : lead >= 0xE0 ? 3 : lead >= 0xC0 ? 2 : 1;
+            if (fill < spec.size() && is_align(spec[fill])) {
+                size = fill + 1;
+            } else if (is_align(spec[0])) {
+                size = 1;
+            }
+        }
+        if (size < spec.size() && spec[size] == '{') {
+            auto close = spec.find('}', size);
+            return close == std::string::npos ? spec.size() : close + 1;
+        }
+        while (size < spec.size() && spec[size] >= '0' && spec[size] <= '9') ++size;
+        return size;
+    }
+
+    // Formats a value with a spec captured by `capture_spec`. `debug` selects
+    // the debug presentation for types that have one, as fmt does for the
+    // elements of ranges and optionals.
+    template<typename T, typename FormatContext>
+    auto format_with_spec(const T &value, ::fmt::string_view spec, bool debug, FormatContext &ctx) -> decltype(ctx.out()) {
+        ::fmt::formatter<T> f;
+        if (debug) set_debug_format(f, 0);
+        ::fmt::format_parse_context parse_ctx(spec);
+        auto it = f.parse(parse_ctx);
+        if (it != spec.data() + spec.size()) {
+            throw ::fmt::format_error("invalid format specifier");
+        }
+        return f.format(value, ctx);
+    }
+
+    template<typename FormatContext>
+    auto format_value(const shimrs::Value &v, ::fmt::string_view spec, bool debug, FormatContext &ctx) -> decltype(ctx.out());
+}
+
+namespace fmt {
+    template<>
+    struct formatter<shimcpp::code_point> {
+        std::string spec_;
+        bool debug_ = false;
+
+        void set_debug_format(bool set = true) {
+            debug_ = set;
+        }
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            auto end = shimcpp::capture_spec(ctx, spec_);
+            // ASCII chars are formatted by fmt itself, so check the spec the
+            // same way to accept and reject the same specs for all chars.
+            formatter<char> check;
+            format_parse_context check_ctx(spec_);
+            if (check.parse(check_ctx) != spec_.data() + spec_.size()) {
+                throw format_error("invalid format specifier");
+            }
+            return end;
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::code_point c, FormatContext &ctx) const -> decltype(ctx.out()) {
+            // Mirror fmt's char handling: integer presentations print the
+            // code point, everything else prints the character itself.
+            char type = spec_.size() > 0 ? spec_.data()[spec_.size() - 1] : (debug_ ? '?' : '\0');
+            string_view spec = spec_;
+            switch (type) {
+                case 'b': case 'B': case 'd': case 'o': case 'x': case 'X':
+                    return shimcpp::format_with_spec(static_cast<unsigned>(c.value), spec, false, ctx);
+                case 'c': case '?':
+                    if (spec.size() > 0) spec = string_view(spec.data(), spec.size() - 1);
+                    break;
+            }
+
+            char buf[4];
+            string_view text(buf, shimcpp::encode_utf8(c.value, buf));
+            if (type != '?') {
+                return shimcpp::format_with_spec(text, spec, false, ctx);
+            }
+            // fmt escapes a char as it does inside a string, only the quotes
+            // differ. Code points from 0x80 on never need the quotes escaped.
+            auto escaped = ::fmt::format("{:?}", text);
+            escaped.front() = escaped.back() = '\'';
+            return shimcpp::format_with_spec(string_view(escaped), spec, false, ctx);
+        }
+    };
+
+    template<>
+    struct formatter<shimcpp::none_text> : formatter<string_view> {
+        void set_debug_format(bool = true) {}
+
+        template<typename FormatContext>
+        auto format(shimcpp::none_text n, FormatContext &ctx) const -> decltype(ctx.out()) {
+            return formatter<string_view>::format(n.text, ctx);
+        }
+    };
+
+    template<>
+    struct formatter<shimcpp::value_ref> {
+        std::string spec_;
+        bool debug_ = false;
+
+        void set_debug_format(bool set = true) {
+            debug_ = set;
+        }
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            return shimcpp::capture_spec(ctx, spec_);
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::value_ref v, FormatContext &ctx) const -> decltype(ctx.out()) {
+            return shimcpp::format_value(*v.value, spec_, debug_, ctx);
+        }
+    };
+
+    template<>
+    struct formatter<shimcpp::seq_ref> {
+        range_formatter<shimcpp::value_ref, char> underlying_;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            return underlying_.parse(ctx);
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::seq_ref s, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto items = s.value->get_seq();
+            std::vector<shimcpp::value_ref> refs;
+            refs.reserve(items.size());
+            for (auto &item : items) {
+                refs.push_back(shimcpp::value_ref{&item});
+            }
+            return underlying_.format(refs, ctx);
+        }
+    };
+
+    // Mirrors fmt's map formatter: `{"a": 1, "b": 2}`, or without the braces
+    // with `{:n}`.
+    template<>
+    struct formatter<shimcpp::map_ref> {
+        bool no_delimiters_ = false;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            auto it = ctx.begin(), end = ctx.end();
+            if (it != end && *it == 'n') {
+                no_delimiters_ = true;
+                ++it;
+            }
+            if (it != end && *it != '}') {
+                throw format_error("invalid format specifier");
+            }
+            return it;
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::map_ref m, FormatContext &ctx) const -> decltype(ctx.out()) {
+            string_view sep(", "), kv_sep(": ");
+            auto out = ctx.out();
+            if (!no_delimiters_) *out++ = '{';
+            auto len = m.value->get_map_len();
+            for (std::size_t i = 0; i < len; ++i) {
+                if (i > 0) out = std::copy(sep.begin(), sep.end(), out);
+                ctx.advance_to(out);
+                out = shimcpp::format_value(m.value->get_map_key(i), {}, true, ctx);
+                out = std::copy(kv_sep.begin(), kv_sep.end(), out);
+                ctx.advance_to(out);
+                out = shimcpp::format_value(m.value->get_map_value(i), {}, true, ctx);
+            }
+            if (!no_delimiters_) *out++ = '}';
+            return out;
+        }
+    };
+
+    // Mirrors fmt's tuple formatter: `(1, "a", true)`, or without the
+    // parentheses with `{:n}`.
+    template<>
+    struct formatter<shimcpp::tuple_ref> {
+        bool no_delimiters_ = false;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            auto it = ctx.begin(), end = ctx.end();
+            if (it != end && *it == 'n') {
+                no_delimiters_ = true;
+                ++it;
+            }
+            if (it != end && *it != '}') {
+                throw format_error("invalid format specifier");
+            }
+            return it;
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::tuple_ref t, FormatContext &ctx) const -> decltype(ctx.out()) {
+            string_view sep(no_delimiters_ ? "" : ", ");
+            auto out = ctx.out();
+            if (!no_delimiters_) *out++ = '(';
+            auto items = t.value->get_tuple();
+            for (std::size_t i = 0; i < items.size(); ++i) {
+                if (i > 0) out = std::copy(sep.begin(), sep.end(), out);
+                ctx.advance_to(out);
+                out = shimcpp::format_value(items[i], {}, true, ctx);
+            }
+            if (!no_delimiters_) *out++ = ')';
+            return out;
+        }
+    };
+
+    // Bytes are formatted as a range of integers, e.g. `{::02x}`, unless the
+    // spec has an `x` or `X` presentation type, which prints them as one
+    // contiguous hex string, e.g. `{:>12.4x}`. Fill, alignment and width
+    // apply to the rendered output of either, e.g. `{:*>20::02x}`.
+    template<>
+    struct formatter<shimcpp::bytes_ref> {
+        range_formatter<std::uint8_t, char> range_;
+        std::string spec_;
+        std::string range_spec_;
+        bool hex_ = false;
+        bool upper_ = false;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            auto spec_end = shimcpp::capture_spec(ctx, spec_);
+            auto padding = shimcpp::padding_size(spec_);
+            auto rest = spec_.substr(padding);
+            if (!rest.empty() && rest[0] != 'n' && rest[0] != ':') {
+                auto type = spec_.back();
+                if (type != 'x' && type != 'X') {
+                    throw format_error("invalid format specifier");
+                }
+                hex_ = true;
+                upper_ = type == 'X';
+                spec_.pop_back();
+                return spec_end;
+            }
+
+            // The captured range spec only has manually indexed nested
+            // fields, so it parses in a context of its own.
+            range_spec_ = rest;
+            spec_.resize(padding);
+            format_parse_context range_ctx(range_spec_);
+            if (range_.parse(range_ctx) != range_spec_.data() + range_spec_.size()) {
+                throw format_error("invalid format specifier");
+            }
+            return spec_end;
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::bytes_ref b, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto bytes = b.value->get_bytes();
+            if (!hex_) {
+                if (spec_.empty()) return range_.format(bytes, ctx);
+                ::fmt::memory_buffer buf;
+                FormatContext range_ctx(::fmt::appender(buf), ctx.args(), ctx.locale());
+                range_.format(bytes, range_ctx);
+                return shimcpp::format_with_spec(string_view(buf.data(), buf.size()), spec_, false, ctx);
+            }
+
+            const char *digits = upper_ ? "0123456789ABCDEF" : "0123456789abcdef";
+            std::string hex;
+            hex.reserve(bytes.size() * 2);
+            for (auto byte : bytes) {
+                hex.push_back(digits[byte >> 4]);
+                hex.push_back(digits[byte & 0xF]);
+            }
+            return shimcpp::format_with_spec(string_view(hex), spec_, false, ctx);
+        }
+    };
+
+    // Formats the date/time's wall clock time in its own time zone through
+    // fmt's `std::tm` formatter.
+    template<>
+    struct formatter<shimcpp::datetime_ref> : formatter<std::tm> {
+        template<typename FormatContext>
+        auto format(shimcpp::datetime_ref t, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto offset = t.value->get_datetime_offset();
+            auto local = static_cast<std::time_t>(t.value->get_datetime_timestamp() + offset);
+            auto zone_str = t.value->get_datetime_zone();
+            // tm_zone has to be null terminated.
+            std::string zone(zone_str.data(), zone_str.size());
+            std::tm tm = ::fmt::gmtime(local);
+            shimcpp::set_tm_zone(tm, offset, zone.c_str(), 0);
+            return formatter<std::tm>::format(tm, ctx);
+        }
+    };
+
+    template<>
+    struct formatter<shimcpp::decimal_ref> {
+        std::string spec_;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            return shimcpp::capture_spec(ctx, spec_);
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::decimal_ref d, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto loc = ctx.locale().template get<std::locale>();
+            auto &punct = std::use_facet<std::numpunct<char>>(loc);
+            auto grouping = punct.grouping();
+            std::string sep(1, punct.thousands_sep()), point(1, punct.decimal_point());
+            if (auto own = dynamic_cast<const shimcpp::numpunct*>(&punct)) {
+                grouping = own->grouping_;
+                sep = own->thousands_sep_;
+                point = own->decimal_point_;
+            }
+            auto text = d.value->format_decimal(
+                rust::Str(spec_.data(), spec_.size()),
+                rust::Slice<const std::uint8_t>(reinterpret_cast<const std::uint8_t*>(grouping.data()), grouping.size()),
+                rust::Str(sep.data(), sep.size()),
+                rust::Str(point.data(), point.size()));
+            return std::copy(text.begin(), text.end(), ctx.out());
+        }
+    };
+
+    template<>
+    struct formatter<shimcpp::styled_ref> : formatter<shimcpp::value_ref> {
+        template<typename FormatContext>
+        auto format(shimcpp::styled_ref s, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto &inner = s.value->get_styled_value();
+            if (!shimrs::colors_enabled()) {
+                return shimcpp::format_value(inner, spec_, debug_, ctx);
+            }
+            auto styled = ::fmt::styled(shimcpp::value_ref{&inner}, shimcpp::to_text_style(s.value->get_style()));
+            return shimcpp::format_with_spec(styled, spec_, debug_, ctx);
+        }
+    };
+
+    // Hands the raw spec to the value's `Format` implementation. Errors it
+    // returns are thrown as `rust::Error` and fail the format call.
+    template<>
+    struct formatter<shimcpp::custom_ref> {
+        std::string spec_;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            return shimcpp::capture_spec(ctx, spec_);
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::custom_ref c, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto text = c.value->format_custom(rust::Str(spec_.data(), spec_.size()));
+            return std::copy(text.begin(), text.end(), ctx.out());
+        }
+    };
+
+    // Display output isn't a debug representation and quoting it as a string
+    // would pass for one, so `?` is rejected.
+    template<>
+    struct formatter<shimcpp::display_ref> : formatter<string_view> {
+        auto parse(format_parse_context &ctx) -> const char* {
+            auto end = formatter<string_view>::parse(ctx);
+            if (end != ctx.begin() && end[-1] == '?') {
+                throw format_error("invalid format specifier for a Display value");
+            }
+            return end;
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::display_ref d, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto text = d.value->render_display();
+            return formatter<string_view>::format(string_view(text.data(), text.size()), ctx);
+        }
+    };
+
+    // The Debug output is already a debug representation, so `?` selects it
+    // without quoting it again.
+    template<>
+    struct formatter<shimcpp::debug_ref> {
+        std::string spec_;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            auto spec_end = shimcpp::capture_spec(ctx, spec_);
+            if (spec_.size() > 0 && spec_.back() == '?') {
+                spec_.pop_back();
+            }
+            return spec_end;
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::debug_ref d, FormatContext &ctx) const -> decltype(ctx.out()) {
+            auto text = d.value->render_debug();
+            return shimcpp::format_with_spec(string_view(text.data(), text.size()), spec_, false, ctx);
+        }
+    };
+
+    template<>
+    struct formatter<shimcpp::optional_ref> {
+        std::string spec_;
+
+        auto parse(format_parse_context &ctx) -> const char* {
+            return shimcpp::capture_spec(ctx, spec_);
+        }
+
+        template<typename FormatContext>
+        auto format(shimcpp::optional_ref o, FormatContext &ctx) const -> decltype(ctx.out()) {
+            string_view prefix("optional(");
+            ctx.advance_to(std::copy(prefix.begin(), prefix.end(), ctx.out()));
+            auto out = shimcpp::format_value(*o.value, spec_, true, ctx);
+            *out++ = ')';
+            return out;
+        }
+    };
+}
+
+namespace shimcpp {
+    // Calls `callback` with `count` units of 10^-digits seconds, so fmt
+    // prints as many fractional digits as the value has.
+    template<typename CB>
+    void with_duration(std::int64_t count, std::uint8_t digits, CB callback) {
+        switch (digits) {
+        case 0:
+            callback(std::chrono::seconds(count));
+            break;
+        case 3:
+            callback(std::chrono::milliseconds(count));
+            break;
+        case 6:
+            callback(std::chrono::microseconds(count));
+            break;
+        default:
+            callback(std::chrono::nanoseconds(count));
+            break;
+        }
+    }
+
     template<typename CB>
     void add_value(const shimrs::Value &v, CB callback) {
         if (v.is_bool()) {
             callback(v.get_bool());
+        } else if (v.is_char()) {
+            auto c = static_cast<char32_t>(v.get_code_point());
+            if (c < 0x80) {
+                callback(static_cast<char>(c));
+            } else {
+                callback(code_point{c});
+            }
         } else if (v.is_int64()) {
-            callback(v.get_int64());
+            callback(static_cast<long long>(v.get_int64()));
+        } else if (v.is_uint64()) {
+            callback(static_cast<unsigned long long>(v.get_uint64()));
+        } else if (v.is_int128() || v.is_uint128()) {
+#if FMT_USE_INT128
+            if (v.is_int128()) {
+                auto bits = (static_cast<unsigned __int128>(v.get_int128_high()) << 64) | v.get_int128_low();
+                callback(static_cast<__int128>(bits));
+            } else {
+                auto bits = (static_cast<unsigned __int128>(v.get_uint128_high()) << 64) | v.get_uint128_low();
+                callback(bits);
+            }
+#else
+            throw ::fmt::format_error("128-bit integers are not supported on this platform");
+#endif
+        } else if (v.is_float32()) {
+            callback(v.get_float32());
         } else if (v.is_float64()) {
             callback(v.get_float64());
+        } else if (v.is_duration()) {
+            with_duration(v.get_duration_count(), v.get_duration_digits(), callback);
+        } else if (v.is_system_time()) {
+            with_duration(v.get_system_time_count(), v.get_system_time_digits(), [&](auto d) {
+                callback(std::chrono::time_point<std::chrono::system_clock, decltype(d)>(d));
+            });
+        } else if (v.is_datetime()) {
+            callback(datetime_ref{&v});
+        } else if (v.is_pointer()) {
+            callback(reinterpret_cast<const void*>(static_cast<std::uintptr_t>(v.get_pointer())));
         } else if (v.is_string()) {
             auto v0 = v.get_string();
             ::fmt::string_view sv(v0.data(), v0.size());
             callback(sv);
+        } else if (v.is_none()) {
+            auto style = static_cast<none_style>(v.get_none_style());
+            callback(none_text{style == none_style::empty ? "" : "none"});
+        } else if (v.is_some()) {
+            auto &inner = v.get_some();
+            if (static_cast<none_style>(v.get_none_style()) == none_style::optional) {
+                callback(optional_ref{&inner});
+            } else {
+                add_value(inner, callback);
+            }
+        } else if (v.is_seq()) {
+            callback(seq_ref{&v});
+        } else if (v.is_map()) {
+            callback(map_ref{&v});
+        } else if (v.is_tuple()) {
+            callback(tuple_ref{&v});
+        } else if (v.is_bytes()) {
+            callback(bytes_ref{&v});
+        } else if (v.is_decimal()) {
+            callback(decimal_ref{&v});
+        } else if (v.is_styled()) {
+            callback(styled_ref{&v});
+        } else if (v.is_custom()) {
+            callback(custom_ref{&v});
+        } else if (v.is_display()) {
+            callback(display_ref{&v});
+        } else if (v.is_debug()) {
+            callback(debug_ref{&v});
         }
     }
 
-    rust::String format(const char* fmt, rust::Slice<const ::shimrs::Arg> args) {
-        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+    template<typename FormatContext>
+    auto format_value(const shimrs::Value &v, ::fmt::string_view spec, bool debug, FormatContext &ctx) -> decltype(ctx.out()) {
+        auto out = ctx.out();
+        add_value(v, [&](auto data) {
+            out = format_with_spec(data, spec, debug, ctx);
+        });
+        return out;
+    }
+
+    inline void push_args(::fmt::dynamic_format_arg_store<::fmt::format_context> &store, rust::Slice<const ::shimrs::Arg> args) {
         store.reserve(args.size(), args.size());
         for (auto& a : args) {
             auto &v = a.get_value();
             if (a.is_named()) {
-                const char *name = static_cast<const char*>(a.get_name_ptr());
+                // fmt takes null terminated names, and the store copies them.
+                auto name_str = a.get_name();
+                std::string name(name_str.data(), name_str.size());
                 auto cb = [&name, &store](auto data) {
-                    store.push_back(::fmt::arg(name, data));
+                    store.push_back(::fmt::arg(name.c_str(), data));
                 };
                 add_value(v, cb);
             } else {
@@ -41,8 +766,209 @@ namespace shimcpp {
                 add_value(v, cb);
             }
         }
+    }
+
+    rust::String format(rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        return fmt::vformat(::fmt::string_view(fmt.data(), fmt.size()), store);
+    }
+
+    // Collects formatted output through `std::back_inserter` and hands it to
+    // a Rust `Writer` a chunk at a time.
+    class chunk_writer {
+    public:
+        using value_type = char;
+
+        explicit chunk_writer(::shimrs::Writer &writer) : writer_(writer) {}
+
+        void push_back(char c) {
+            if (size_ == sizeof(buf_)) flush();
+            buf_[size_++] = c;
+        }
+
+        // Used by fmt to copy its own buffer.
+        void append(const char *begin, const char *end) {
+            while (begin != end) {
+                if (size_ == sizeof(buf_)) flush();
+                auto n = std::min(static_cast<std::size_t>(end - begin), sizeof(buf_) - size_);
+                std::copy(begin, begin + n, buf_ + size_);
+                size_ += n;
+                begin += n;
+            }
+        }
+
+        void flush() {
+            if (size_ == 0) return;
+            writer_.write(rust::Slice<const std::uint8_t>(reinterpret_cast<const std::uint8_t*>(buf_), size_));
+            size_ = 0;
+        }
+
+    private:
+        ::shimrs::Writer &writer_;
+        char buf_[4096];
+        std::size_t size_ = 0;
+    };
+
+    void format_to(::shimrs::Writer &writer, rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        chunk_writer out(writer);
+        fmt::vformat_to(std::back_inserter(out), ::fmt::string_view(fmt.data(), fmt.size()), store);
+        out.flush();
+    }
+
+    // Counts the output written through `std::back_inserter` without storing
+    // it.
+    struct size_counter {
+        using value_type = char;
+        std::size_t size = 0;
+
+        void push_back(char) {
+            ++size;
+        }
+
+        void append(const char *begin, const char *end) {
+            size += static_cast<std::size_t>(end - begin);
+        }
+    };
+
+    // fmt's estimate of the number of terminal columns `s` takes, as used for
+    // width and precision.
+    inline std::size_t display_width(::fmt::string_view s) {
+#if FMT_VERSION >= 120000
+        std::size_t width = 0;
+        ::fmt::detail::for_each_codepoint(s, [&width](std::uint32_t cp, ::fmt::string_view) {
+            width += ::fmt::detail::display_width_of(cp);
+            return true;
+        });
+        return width;
+#else
+        return ::fmt::detail::compute_width(s);
+#endif
+    }
+
+    // Returns the length of an incomplete UTF-8 sequence at the end of
+    // [begin, end).
+    inline std::size_t incomplete_suffix(const char *begin, const char *end) {
+        auto size = static_cast<std::size_t>(end - begin);
+        for (std::size_t n = 1; n <= 3 && n <= size; ++n) {
+            auto c = static_cast<unsigned char>(*(end - n));
+            if ((c & 0xC0) == 0x80) continue;
+            std::size_t len = c >= 0xF0 ? 4 : c >= 0xE0 ? 3 : c >= 0xC0 ? 2 : 1;
+            return len > n ? n : 0;
+        }
+        return 0;
+    }
+
+    // Sums the display width of the output written through
+    // `std::back_inserter` without storing it. A UTF-8 sequence split across
+    // writes is held back until it's complete.
+    struct width_counter {
+        using value_type = char;
+        std::size_t width = 0;
+        std::string partial;
+
+        void push_back(char c) {
+            append(&c, &c + 1);
+        }
+
+        void append(const char *begin, const char *end) {
+            std::string joined;
+            if (!partial.empty()) {
+                joined.swap(partial);
+                joined.append(begin, end);
+                begin = joined.data();
+                end = begin + joined.size();
+            }
+            auto complete = end - incomplete_suffix(begin, end);
+            width += display_width(::fmt::string_view(begin, static_cast<std::size_t>(complete - begin)));
+            partial.assign(complete, end);
+        }
 
-        return fmt::vformat(fmt, store);
+        std::size_t finish() {
+            width += display_width(::fmt::string_view(partial.data(), partial.size()));
+            partial.clear();
+            return width;
+        }
+    };
+
+    std::size_t formatted_size(rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        size_counter counter;
+        fmt::vformat_to(std::back_inserter(counter), ::fmt::string_view(fmt.data(), fmt.size()), store);
+        return counter.size;
+    }
+
+    std::size_t formatted_width(rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        width_counter counter;
+        fmt::vformat_to(std::back_inserter(counter), ::fmt::string_view(fmt.data(), fmt.size()), store);
+        return counter.finish();
+    }
+
+    std::size_t format_to_n(rust::Slice<std::uint8_t> buf, rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        auto result = fmt::vformat_to_n(reinterpret_cast<char*>(buf.data()), buf.size(), ::fmt::string_view(fmt.data(), fmt.size()), store);
+        return result.size;
+    }
+
+    // Flushes after printing, so output printed by Rust afterwards isn't
+    // written before it.
+    inline void print_to(std::FILE *f, rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        fmt::vprint(f, ::fmt::string_view(fmt.data(), fmt.size()), store);
+        if (std::fflush(f) != 0) {
+            throw std::system_error(errno, std::generic_category(), "cannot flush output");
+        }
+    }
+
+    void print(rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        print_to(stdout, fmt, args);
+    }
+
+    void eprint(rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        print_to(stderr, fmt, args);
+    }
+
+    // Formats each field's argument with its spec directly, without a
+    // format string or an argument store.
+    rust::String render_template(const ::shimrs::Template &tmpl, rust::Slice<const std::size_t> fields, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::memory_buffer buf;
+        ::fmt::format_context ctx(::fmt::appender(buf), {});
+        std::size_t field = 0;
+        for (std::size_t i = 0, n = tmpl.get_piece_count(); i < n; ++i) {
+            auto text = tmpl.get_text(i);
+            if (tmpl.is_field(i)) {
+                auto &v = args[fields[field++]].get_value();
+                ctx.advance_to(format_value(v, ::fmt::string_view(text.data(), text.size()), false, ctx));
+            } else {
+                buf.append(text.data(), text.data() + text.size());
+            }
+        }
+        return ::fmt::to_string(buf);
+    }
+
+    void check_spec(const ::shimrs::Value &value, rust::Str spec) {
+        ::fmt::memory_buffer buf;
+        ::fmt::format_context ctx(::fmt::appender(buf), {});
+        format_value(value, ::fmt::string_view(spec.data(), spec.size()), false, ctx);
+    }
+
+    rust::String format_styled(const ::shimrs::Style &style, rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        return fmt::vformat(to_text_style(style), ::fmt::string_view(fmt.data(), fmt.size()), store);
+    }
+
+    rust::String format_with_locale(const ::shimrs::Locale &locale, rust::Str fmt, rust::Slice<const ::shimrs::Arg> args) {
+        ::fmt::dynamic_format_arg_store<::fmt::format_context> store;
+        push_args(store, args);
+        return fmt::vformat(make_locale(locale), ::fmt::string_view(fmt.data(), fmt.size()), store);
     }
 }
-#endif
\ No newline at end of file
+#endif
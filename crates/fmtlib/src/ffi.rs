@@ -1,6 +1,17 @@
 type Arg<'s> = crate::fmt::Arg<'s>;
+type Locale = crate::fmt::Locale;
+type Style = crate::fmt::Style;
+type Template = crate::fmt::Template;
 type Value<'s> = crate::fmt::Value<'s>;
+type Writer<'a> = crate::fmt::Writer<'a>;
 
+fn colors_enabled() -> bool {
+    crate::fmt::colors_enabled()
+}
+
+// The generated code passes references to Rust types through
+// `ptr::from_ref` and `ptr::from_mut`, newer than the MSRV.
+#[allow(clippy::incompatible_msrv)]
 #[cxx::bridge]
 pub mod fmt {
     #[namespace = "shimrs"]
@@ -8,14 +19,106 @@ pub mod fmt {
         type Value<'s>;
 
         fn is_bool(self: &Value) -> bool;
+        fn is_char(self: &Value) -> bool;
+        fn is_float32(self: &Value) -> bool;
         fn is_float64(self: &Value) -> bool;
         fn is_int64(self: &Value) -> bool;
+        fn is_int128(self: &Value) -> bool;
+        fn is_uint64(self: &Value) -> bool;
+        fn is_uint128(self: &Value) -> bool;
         fn is_string(self: &Value) -> bool;
+        fn is_none(self: &Value) -> bool;
+        fn is_some(self: &Value) -> bool;
+        fn is_seq(self: &Value) -> bool;
+        fn is_map(self: &Value) -> bool;
+        fn is_tuple(self: &Value) -> bool;
+        fn is_pointer(self: &Value) -> bool;
+        fn is_bytes(self: &Value) -> bool;
+        fn is_duration(self: &Value) -> bool;
+        fn is_system_time(self: &Value) -> bool;
+        fn is_datetime(self: &Value) -> bool;
+        fn is_decimal(self: &Value) -> bool;
+        fn is_styled(self: &Value) -> bool;
+        fn is_custom(self: &Value) -> bool;
+        fn is_display(self: &Value) -> bool;
+        fn is_debug(self: &Value) -> bool;
 
         fn get_bool(self: &Value) -> Result<bool>;
+        fn get_code_point(self: &Value) -> Result<u32>;
         fn get_int64(self: &Value) -> Result<i64>;
+        fn get_int128_high(self: &Value) -> Result<u64>;
+        fn get_int128_low(self: &Value) -> Result<u64>;
+        fn get_uint64(self: &Value) -> Result<u64>;
+        fn get_uint128_high(self: &Value) -> Result<u64>;
+        fn get_uint128_low(self: &Value) -> Result<u64>;
+        fn get_float32(self: &Value) -> Result<f32>;
         fn get_float64(self: &Value) -> Result<f64>;
         unsafe fn get_string<'s>(self: &'s Value<'s>) -> Result<&'s str>;
+        unsafe fn get_bytes<'s>(self: &'s Value<'s>) -> Result<&'s [u8]>;
+        unsafe fn get_some<'s>(self: &'s Value<'s>) -> Result<&'s Value<'s>>;
+        fn get_none_style(self: &Value) -> Result<u8>;
+        unsafe fn get_seq<'s>(self: &'s Value<'s>) -> Result<&'s [Value<'s>]>;
+        unsafe fn get_tuple<'s>(self: &'s Value<'s>) -> Result<&'s [Value<'s>]>;
+        fn get_map_len(self: &Value) -> Result<usize>;
+        fn get_pointer(self: &Value) -> Result<usize>;
+        fn get_duration_count(self: &Value) -> Result<i64>;
+        fn get_duration_digits(self: &Value) -> Result<u8>;
+        fn get_system_time_count(self: &Value) -> Result<i64>;
+        fn get_system_time_digits(self: &Value) -> Result<u8>;
+        fn get_datetime_timestamp(self: &Value) -> Result<i64>;
+        fn get_datetime_offset(self: &Value) -> Result<i32>;
+        unsafe fn get_datetime_zone<'s>(self: &'s Value<'s>) -> Result<&'s str>;
+        unsafe fn get_map_key<'s>(self: &'s Value<'s>, index: usize) -> Result<&'s Value<'s>>;
+        unsafe fn get_map_value<'s>(self: &'s Value<'s>, index: usize) -> Result<&'s Value<'s>>;
+        unsafe fn get_styled_value<'s>(self: &'s Value<'s>) -> Result<&'s Value<'s>>;
+        unsafe fn get_style<'s>(self: &'s Value<'s>) -> Result<&'s Style>;
+
+        fn format_decimal(
+            self: &Value,
+            spec: &str,
+            grouping: &[u8],
+            thousands_sep: &str,
+            decimal_point: &str,
+        ) -> Result<String>;
+        fn format_custom(self: &Value, spec: &str) -> Result<String>;
+        fn render_display(self: &Value) -> Result<String>;
+        fn render_debug(self: &Value) -> Result<String>;
+
+        fn colors_enabled() -> bool;
+    }
+
+    #[namespace = "shimrs"]
+    extern "Rust" {
+        type Style;
+
+        fn get_fg(self: &Style) -> u32;
+        fn get_bg(self: &Style) -> u32;
+        fn get_emphasis(self: &Style) -> u8;
+    }
+
+    #[namespace = "shimrs"]
+    extern "Rust" {
+        type Locale;
+
+        fn get_grouping(self: &Locale) -> &[u8];
+        fn get_thousands_sep(self: &Locale) -> u32;
+        fn get_decimal_point(self: &Locale) -> u32;
+    }
+
+    #[namespace = "shimrs"]
+    extern "Rust" {
+        type Template;
+
+        fn get_piece_count(self: &Template) -> usize;
+        fn is_field(self: &Template, index: usize) -> bool;
+        fn get_text(self: &Template, index: usize) -> &str;
+    }
+
+    #[namespace = "shimrs"]
+    extern "Rust" {
+        type Writer<'a>;
+
+        fn write(self: &mut Writer, chunk: &[u8]) -> Result<()>;
     }
 
     #[namespace = "shimrs"]
@@ -25,7 +128,7 @@ pub mod fmt {
         fn is_named(self: &Arg) -> bool;
         fn is_positional(self: &Arg) -> bool;
 
-        unsafe fn get_name_ptr<'s>(self: &'s Arg<'s>) -> Result<*const c_char>;
+        unsafe fn get_name<'s>(self: &'s Arg<'s>) -> Result<&'s str>;
         unsafe fn get_value<'s>(self: &'s Arg<'s>) -> &'s Value<'s>;
     }
 
@@ -34,30 +137,67 @@ pub mod fmt {
         include!("fmtlib/include/shim.h");
 
         /// Format a string using [fmtlib](https://fmt.dev)
-        #[allow(dead_code)]
-        pub(crate) unsafe fn format(fmt: *const c_char, args: &[Arg]) -> Result<String>;
+        pub(crate) fn format(fmt: &str, args: &[Arg]) -> Result<String>;
+
+        /// Format a string and render it with a text style
+        pub(crate) fn format_styled(style: &Style, fmt: &str, args: &[Arg]) -> Result<String>;
+
+        /// Format a string, writing the output to a Rust writer in chunks
+        pub(crate) fn format_to(writer: &mut Writer, fmt: &str, args: &[Arg]) -> Result<()>;
+
+        /// Format a string into a buffer, truncating the output to its size.
+        /// Returns the size of the untruncated output
+        pub(crate) fn format_to_n(buf: &mut [u8], fmt: &str, args: &[Arg]) -> Result<usize>;
+
+        /// Returns the size of a formatted string without formatting it into memory
+        pub(crate) fn formatted_size(fmt: &str, args: &[Arg]) -> Result<usize>;
+
+        /// Returns the display width of a formatted string without formatting it
+        /// into memory
+        pub(crate) fn formatted_width(fmt: &str, args: &[Arg]) -> Result<usize>;
+
+        /// Format a string and print it to stdout
+        pub(crate) fn print(fmt: &str, args: &[Arg]) -> Result<()>;
+
+        /// Format a string and print it to stderr
+        pub(crate) fn eprint(fmt: &str, args: &[Arg]) -> Result<()>;
+
+        /// Format a parsed template. `fields` holds the index of the argument
+        /// each field formats
+        pub(crate) fn render_template(
+            tmpl: &Template,
+            fields: &[usize],
+            args: &[Arg],
+        ) -> Result<String>;
+
+        /// Format a value with a spec, discarding the output. Fails if the
+        /// spec is invalid for the value
+        pub(crate) fn check_spec(value: &Value, spec: &str) -> Result<()>;
+
+        /// Format a string, applying a locale to `L` specs
+        pub(crate) fn format_with_locale(
+            locale: &Locale,
+            fmt: &str,
+            args: &[Arg],
+        ) -> Result<String>;
     }
 }
 
 #[cfg(test)]
 mod ffi_tests {
-    use std::ffi::CStr;
-
     use super::*;
 
     #[test]
     fn test_name() {
         let mut args = fmtlib_proc_macros::rt_format_args!(String::from("string"), "static str", 12, "named": "named arg");
-        let fmt = CStr::from_bytes_with_nul(b"test '{}' '{}' '{}' '{named}'\0").unwrap();
-        match unsafe { fmt::format(fmt.as_ptr(), args.as_mut_slice()) } {
+        match fmt::format("test '{}' '{}' '{}' '{named}'", args.as_mut_slice()) {
             Ok(ref v) => {
                 assert_eq!(v, "test 'string' 'static str' '12' 'named arg'");
             }
             Err(e) => panic!("error from fmtlib: {e}"),
         }
         let mut args = fmtlib_proc_macros::rt_format_args!(1, 3, b: 2);
-        let fmt = CStr::from_bytes_with_nul(b"test '{0}' '{b}' '{1}'\0").unwrap();
-        match unsafe { fmt::format(fmt.as_ptr(), args.as_mut_slice()) } {
+        match fmt::format("test '{0}' '{b}' '{1}'", args.as_mut_slice()) {
             Ok(ref v) => {
                 assert_eq!(v, "test '1' '2' '3'");
             }
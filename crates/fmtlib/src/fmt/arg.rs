@@ -1,11 +1,13 @@
-use std::ffi::CString;
+use std::borrow::Cow;
 
 use super::{errors, Value};
 
 #[derive(Clone, Debug)]
 pub enum Arg<'s> {
     Pos(Value<'s>),
-    Named(CString, Value<'s>),
+    /// A named argument. The name is checked when the argument is formatted,
+    /// see [`Arg::validate_name`].
+    Named(Cow<'s, str>, Value<'s>),
 }
 
 impl<'arg> Arg<'arg> {
@@ -13,9 +15,8 @@ impl<'arg> Arg<'arg> {
         Self::Pos(arg.into())
     }
 
-    pub fn named<'s: 'arg, S: Into<Vec<u8>>, T: Into<Value<'s>>>(name: S, arg: T) -> Self {
-        let n = CString::new(name).expect("invalid argument name");
-        Self::Named(n, arg.into())
+    pub fn named<'s: 'arg, S: Into<Cow<'arg, str>>, T: Into<Value<'s>>>(name: S, arg: T) -> Self {
+        Self::Named(name.into(), arg.into())
     }
 }
 
@@ -28,7 +29,7 @@ impl Arg<'_> {
         matches!(self, Arg::Pos(..))
     }
 
-    pub fn get_name(&self) -> Result<&std::ffi::CStr, errors::Error> {
+    pub fn get_name(&self) -> Result<&str, errors::Error> {
         match self {
             Arg::Named(name, _) => Ok(name),
             _ => Err(errors::Error::InvalidOperation(
@@ -37,16 +38,25 @@ impl Arg<'_> {
         }
     }
 
-    pub fn get_name_ptr(&self) -> Result<*const std::ffi::c_char, errors::Error> {
-        match self {
-            Arg::Named(name, _) => Ok(name.as_ptr()),
-            _ => Err(errors::Error::InvalidOperation(
-                "positional argument has no name",
-            )),
+    /// Checks that a named argument's name can be referenced from a format
+    /// string, i.e. that it's an ASCII identifier as fmt parses them.
+    pub fn validate_name(&self) -> Result<(), errors::Error> {
+        let Arg::Named(name, _) = self else {
+            return Ok(());
+        };
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if valid {
+            Ok(())
+        } else {
+            Err(errors::Error::InvalidArgName(name.to_string()))
         }
     }
 
-    pub fn get_value(&self) -> &Value {
+    pub fn get_value(&self) -> &Value<'_> {
         match self {
             Arg::Named(_, value) => value,
             Arg::Pos(value) => value,
@@ -67,21 +77,17 @@ pub trait IntoArgs<'a> {
     fn into_args(self) -> Vec<super::Arg<'a>>;
 }
 
-impl<'a, T> IntoArgs<'a> for Vec<T>
-where
-    T: Into<super::Arg<'a>>,
-{
+// Collections of values convert into a single sequence argument, so only
+// collections of `Arg`s are spread into multiple arguments.
+impl<'a> IntoArgs<'a> for Vec<super::Arg<'a>> {
     fn into_args(self) -> Vec<super::Arg<'a>> {
-        self.into_iter().map(|e| e.into()).collect()
+        self
     }
 }
 
-impl<'a, T> IntoArgs<'a> for &'a [T]
-where
-    T: Into<super::Arg<'a>> + Clone,
-{
+impl<'a> IntoArgs<'a> for &'a [super::Arg<'a>] {
     fn into_args(self) -> Vec<super::Arg<'a>> {
-        self.into_iter().map(|e| e.clone().into()).collect()
+        self.to_vec()
     }
 }
 
@@ -93,3 +99,8 @@ where
         vec![self.into()]
     }
 }
+
+/// Validates the names of the named arguments in `args`.
+pub(crate) fn validate_names(args: &[Arg]) -> Result<(), errors::Error> {
+    args.iter().try_for_each(Arg::validate_name)
+}
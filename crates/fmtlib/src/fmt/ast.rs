@@ -0,0 +1,340 @@
+use std::{fmt, ops::Range, str::FromStr};
+
+use super::{
+    errors::{self, FormatError, FormatErrorKind},
+    ArgRef, FormatSpec,
+};
+
+/// A format string parsed into literal text and replacement fields, e.g. to
+/// inspect or rewrite templates. Spans are byte ranges in the parsed string.
+///
+/// ```
+/// # use fmtlib::fmt::{ArgRef, Ast, Segment};
+/// let mut ast = Ast::parse("{{{name:>8}}}").unwrap();
+/// let Segment::Field(field) = &mut ast.segments[1] else { panic!() };
+/// assert_eq!((&field.arg, field.span.clone()), (&ArgRef::Name("name".into()), 2..11));
+/// field.arg = ArgRef::Index(0);
+/// assert_eq!(ast.to_string(), "{{{0:>8}}}");
+/// ```
+///
+/// Only the syntax is checked. Argument indexing, e.g. mixing `{}` and `{0}`,
+/// and whether a spec suits its argument are checked when formatting.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ast {
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Literal(Literal),
+    Field(ReplacementField),
+}
+
+/// Literal text. Printed with its braces escaped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Literal {
+    /// The text with `{{` and `}}` unescaped.
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+/// A replacement field, e.g. `{name:>8}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplacementField {
+    pub arg: ArgRef,
+    /// The span of the argument id, empty for `{}`.
+    pub arg_span: Range<usize>,
+    /// The spec, if the field has a `:`.
+    pub spec: Option<FieldSpec>,
+    /// The span of the whole field, braces included.
+    pub span: Range<usize>,
+}
+
+/// The spec of a replacement field, i.e. the text after the `:`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldSpec {
+    /// The spec as written. This is what's printed, so set it to rewrite the
+    /// spec, e.g. from a modified [`FormatSpec`].
+    pub text: String,
+    pub span: Range<usize>,
+    /// The standard fields, if the spec is in fmt's standard mini-language.
+    /// Chrono and range specs aren't.
+    pub format: Option<FormatSpec>,
+    /// The arguments the width and precision are read from, e.g. `{}` and
+    /// `{p}` in `{:{}.{p}}`.
+    pub args: Vec<SpecArg>,
+}
+
+/// An argument a spec reads its width or precision from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpecArg {
+    pub arg: ArgRef,
+    /// The span of the nested field, braces included.
+    pub span: Range<usize>,
+}
+
+impl Ast {
+    /// Parses a format string in [fmtlib](https://fmt.dev)'s syntax.
+    pub fn parse(source: &str) -> Result<Self, errors::Error> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut literal_start = 0;
+
+        let mut pos = 0;
+        while let Some(i) = source[pos..].find(['{', '}']) {
+            let brace = pos + i;
+            literal.push_str(&source[pos..brace]);
+            let after = &source[brace + 1..];
+            if source.as_bytes()[brace] == b'}' {
+                if !after.starts_with('}') {
+                    return Err(invalid(
+                        FormatErrorKind::UnmatchedBrace,
+                        "unmatched '}' in format string",
+                        brace..brace + 1,
+                    ));
+                }
+                literal.push('}');
+                pos = brace + 2;
+                continue;
+            }
+            if after.starts_with('{') {
+                literal.push('{');
+                pos = brace + 2;
+                continue;
+            }
+
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(Literal {
+                    text: std::mem::take(&mut literal),
+                    span: literal_start..brace,
+                }));
+            }
+            let end =
+                brace + 1 + field_end(after).ok_or_else(|| missing_brace(brace, source.len()))?;
+            segments.push(Segment::Field(parse_field(source, brace + 1..end)?));
+            pos = end + 1;
+            literal_start = pos;
+        }
+        literal.push_str(&source[pos..]);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(Literal {
+                text: literal,
+                span: literal_start..source.len(),
+            }));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &ReplacementField> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Field(field) => Some(field),
+            Segment::Literal(_) => None,
+        })
+    }
+}
+
+impl FromStr for Ast {
+    type Err = errors::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Ast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.segments
+            .iter()
+            .try_for_each(|segment| write!(f, "{segment}"))
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Literal(literal) => write!(f, "{literal}"),
+            Segment::Field(field) => write!(f, "{field}"),
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.text.as_str();
+        while let Some(i) = rest.find(['{', '}']) {
+            // Writes the brace twice.
+            f.write_str(&rest[..=i])?;
+            f.write_str(&rest[i..=i])?;
+            rest = &rest[i + 1..];
+        }
+        f.write_str(rest)
+    }
+}
+
+impl fmt::Display for ReplacementField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.spec {
+            Some(spec) => write!(f, "{{{}:{}}}", self.arg, spec.text),
+            None => write!(f, "{{{}}}", self.arg),
+        }
+    }
+}
+
+fn invalid(kind: FormatErrorKind, msg: &str, span: Range<usize>) -> errors::Error {
+    errors::Error::InvalidTemplate(FormatError::new(kind, msg).with_span(span))
+}
+
+/// The error for the `{` at `brace` not being closed before `end`.
+fn missing_brace(brace: usize, end: usize) -> errors::Error {
+    invalid(
+        FormatErrorKind::UnmatchedBrace,
+        "missing '}' in format string",
+        brace..end,
+    )
+}
+
+/// Returns the position of the `}` closing a replacement field, skipping the
+/// fields nested in its spec.
+fn field_end(field: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, b) in field.bytes().enumerate() {
+        match b {
+            b'{' => depth += 1,
+            b'}' if depth == 0 => return Some(i),
+            b'}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_arg(source: &str, span: Range<usize>) -> Result<ArgRef, errors::Error> {
+    ArgRef::parse(&source[span.clone()]).map_err(|e| {
+        let msg = match e {
+            errors::Error::InvalidSpec(msg) => msg,
+            e => e.to_string(),
+        };
+        invalid(FormatErrorKind::InvalidArgId, &msg, span)
+    })
+}
+
+/// Parses the replacement field between the braces at `span`.
+fn parse_field(source: &str, span: Range<usize>) -> Result<ReplacementField, errors::Error> {
+    let field = &source[span.clone()];
+    let id_len = field.find(':').unwrap_or(field.len());
+    let arg_span = span.start..span.start + id_len;
+    let arg = parse_arg(source, arg_span.clone())?;
+
+    let spec = if id_len < field.len() {
+        let spec_span = arg_span.end + 1..span.end;
+        let text = &source[spec_span.clone()];
+        let mut args = Vec::new();
+        let mut pos = spec_span.start;
+        while let Some(i) = source[pos..spec_span.end].find('{') {
+            let start = pos + i;
+            let end = start
+                + source[start..spec_span.end]
+                    .find('}')
+                    .ok_or_else(|| missing_brace(start, spec_span.end))?;
+            args.push(SpecArg {
+                arg: parse_arg(source, start + 1..end)?,
+                span: start..end + 1,
+            });
+            pos = end + 1;
+        }
+        Some(FieldSpec {
+            text: text.to_string(),
+            span: spec_span,
+            format: FormatSpec::parse(text).ok(),
+            args,
+        })
+    } else {
+        None
+    };
+
+    Ok(ReplacementField {
+        arg,
+        arg_span,
+        spec,
+        span: span.start - 1..span.end + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let ast = Ast::parse("a{{{:>{w}}é{0:%H}").unwrap();
+        assert_eq!(
+            ast.segments,
+            [
+                Segment::Literal(Literal {
+                    text: "a{".into(),
+                    span: 0..3,
+                }),
+                Segment::Field(ReplacementField {
+                    arg: ArgRef::Auto,
+                    arg_span: 4..4,
+                    spec: Some(FieldSpec {
+                        text: ">{w}".into(),
+                        span: 5..9,
+                        format: Some(FormatSpec::parse(">{w}").unwrap()),
+                        args: vec![SpecArg {
+                            arg: ArgRef::Name("w".into()),
+                            span: 6..9,
+                        }],
+                    }),
+                    span: 3..10,
+                }),
+                Segment::Literal(Literal {
+                    text: "é".into(),
+                    span: 10..12,
+                }),
+                Segment::Field(ReplacementField {
+                    arg: ArgRef::Index(0),
+                    arg_span: 13..14,
+                    spec: Some(FieldSpec {
+                        text: "%H".into(),
+                        span: 15..17,
+                        format: None,
+                        args: Vec::new(),
+                    }),
+                    span: 12..18,
+                }),
+            ]
+        );
+
+        assert!(Ast::parse("").unwrap().segments.is_empty());
+        for source in [
+            "{", "}", "a}b", "{0", "{:{}", "{:{{}}}", "{a-b}", "{:{a-b}}",
+        ] {
+            assert!(Ast::parse(source).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        for source in [
+            "",
+            "{{}}",
+            "a{}b{:}c{0:.05}",
+            "{name:*^+#010.{p}Lx}",
+            "{::#x} {:%Y-%m-%d}",
+            "}}é{:<<{}.{}}{{",
+        ] {
+            assert_eq!(Ast::parse(source).unwrap().to_string(), source);
+        }
+
+        let mut ast = Ast::parse("{{{}}} {:x}").unwrap();
+        for segment in &mut ast.segments {
+            match segment {
+                Segment::Literal(literal) => literal.text = literal.text.replace('}', "}}"),
+                Segment::Field(field) => field.arg = ArgRef::Name("n".into()),
+            }
+        }
+        assert_eq!(ast.to_string(), "{{{n}}}}} {n:x}");
+    }
+}
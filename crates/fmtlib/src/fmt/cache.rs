@@ -0,0 +1,241 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use super::Template;
+
+/// Statistics of the template cache, see [`set_template_cache_capacity`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TemplateCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of cached templates.
+    pub len: usize,
+    pub capacity: usize,
+}
+
+struct Entry {
+    template: Arc<Template>,
+    last_used: u64,
+}
+
+/// A least recently used cache of parsed templates, keyed by their text.
+struct TemplateCache {
+    capacity: usize,
+    entries: BTreeMap<String, Entry>,
+    /// The cached texts by the time they were last used, oldest first.
+    order: BTreeMap<u64, String>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl TemplateCache {
+    const fn new() -> Self {
+        Self {
+            capacity: 0,
+            entries: BTreeMap::new(),
+            order: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn get(&mut self, source: &str) -> Option<Arc<Template>> {
+        let Some(entry) = self.entries.get_mut(source) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.clock += 1;
+        if let Some(key) = self.order.remove(&entry.last_used) {
+            self.order.insert(self.clock, key);
+        }
+        entry.last_used = self.clock;
+        Some(entry.template.clone())
+    }
+
+    fn insert(&mut self, template: Arc<Template>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        let source = template.source().to_string();
+        let entry = Entry {
+            template,
+            last_used: self.clock,
+        };
+        if let Some(old) = self.entries.insert(source.clone(), entry) {
+            self.order.remove(&old.last_used);
+        }
+        self.order.insert(self.clock, source);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some((_, source)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&source);
+        }
+    }
+
+    /// Drops the cached templates and resets the counters, keeping the
+    /// capacity.
+    fn clear(&mut self) {
+        *self = Self {
+            capacity: self.capacity,
+            ..Self::new()
+        };
+    }
+
+    /// Returns the parsed template for `source`, parsing and caching it on a
+    /// miss. Returns None if the cache is disabled or `source` doesn't parse.
+    fn get_or_parse(cache: &Mutex<Self>, source: &str) -> Option<Arc<Template>> {
+        let mut guard = lock(cache);
+        if guard.capacity == 0 {
+            return None;
+        }
+        if let Some(template) = guard.get(source) {
+            return Some(template);
+        }
+        drop(guard);
+        // Parsed without the lock, so other threads aren't blocked.
+        let template = Arc::new(Template::parse(source).ok()?);
+        lock(cache).insert(template.clone());
+        Some(template)
+    }
+
+    fn stats(&self) -> TemplateCacheStats {
+        TemplateCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            len: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+static CACHE: Mutex<TemplateCache> = Mutex::new(TemplateCache::new());
+
+// The cache holds no invariants a panic could break, so a poisoned lock is
+// still usable.
+fn lock(cache: &Mutex<TemplateCache>) -> MutexGuard<'_, TemplateCache> {
+    cache.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Sets the number of templates cached by [`format`](super::format), and so
+/// by [`rt_format!`](super::rt_format). Repeated format strings are then
+/// parsed once and rendered as a [`Template`].
+///
+/// The cache is disabled by default. A capacity of 0 disables it and drops
+/// the cached templates; a smaller capacity drops the least recently used
+/// ones.
+pub fn set_template_cache_capacity(capacity: usize) {
+    let mut cache = lock(&CACHE);
+    cache.capacity = capacity;
+    cache.evict();
+}
+
+/// Returns the template cache's counters and size.
+pub fn template_cache_stats() -> TemplateCacheStats {
+    lock(&CACHE).stats()
+}
+
+/// Drops the cached templates and resets the counters, keeping the capacity.
+pub fn clear_template_cache() {
+    lock(&CACHE).clear();
+}
+
+/// Looks `source` up in the global cache. On None, the caller formats
+/// without a template and fmt reports any error.
+pub(crate) fn cached_template(source: &str) -> Option<Arc<Template>> {
+    TemplateCache::get_or_parse(&CACHE, source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fmt::{rt_format_args, Arg};
+
+    fn insert(cache: &mut TemplateCache, source: &str) {
+        let template = Template::parse(source).unwrap();
+        cache.insert(Arc::new(template));
+    }
+
+    #[test]
+    fn test_lru() {
+        let mut cache = TemplateCache::new();
+        insert(&mut cache, "{}");
+        assert_eq!(cache.stats().len, 0);
+
+        cache.capacity = 2;
+        insert(&mut cache, "a{}");
+        insert(&mut cache, "b{}");
+        assert!(cache.get("a{}").is_some());
+        insert(&mut cache, "c{}");
+        assert!(cache.get("b{}").is_none());
+        assert_eq!(cache.get("a{}").unwrap().source(), "a{}");
+        assert!(cache.get("c{}").is_some());
+        assert_eq!(
+            cache.stats(),
+            TemplateCacheStats {
+                hits: 3,
+                misses: 1,
+                len: 2,
+                capacity: 2
+            }
+        );
+
+        cache.capacity = 1;
+        cache.evict();
+        assert!(cache.get("a{}").is_none());
+        assert!(cache.get("c{}").is_some());
+        assert_eq!(cache.order.len(), 1);
+    }
+
+    #[test]
+    fn test_template_cache() {
+        // A cache of its own, the global one is shared with other tests.
+        let cache = Mutex::new(TemplateCache {
+            capacity: 16,
+            ..TemplateCache::new()
+        });
+        for i in 0..3 {
+            let template = TemplateCache::get_or_parse(&cache, "cached {:>3} {name}").unwrap();
+            let x = template.render(&rt_format_args!(i, name: "a"));
+            assert_eq!(x.expect("formatting failed"), format!("cached   {i} a"));
+        }
+        assert_eq!(
+            lock(&cache).stats(),
+            TemplateCacheStats {
+                hits: 2,
+                misses: 1,
+                len: 1,
+                capacity: 16
+            }
+        );
+
+        let template = TemplateCache::get_or_parse(&cache, "{:d}").unwrap();
+        assert!(template.render(&rt_format_args!("a")).is_err());
+        // Invalid templates aren't cached, fmt reports their errors.
+        for source in ["{0} {}", "{"] {
+            assert!(TemplateCache::get_or_parse(&cache, source).is_none());
+        }
+        assert_eq!(lock(&cache).stats().len, 2);
+
+        lock(&cache).clear();
+        assert_eq!(
+            lock(&cache).stats(),
+            TemplateCacheStats {
+                capacity: 16,
+                ..TemplateCacheStats::default()
+            }
+        );
+        lock(&cache).capacity = 0;
+        assert!(TemplateCache::get_or_parse(&cache, "{}").is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use super::{errors, FormatSpec, Value};
+
+/// Formatting for user-defined types, called back from fmt with the spec of
+/// the replacement field the value is formatted in.
+///
+/// ```
+/// # use fmtlib::fmt::{errors, rt_format, Format, Formatter};
+/// use std::fmt::Write;
+///
+/// struct Money(i64);
+///
+/// impl Format for Money {
+///     fn format(&self, f: &mut Formatter<'_>) -> Result<(), errors::Error> {
+///         match f.raw_spec() {
+///             "" => write!(f, "{}.{:02}", self.0 / 100, self.0 % 100)?,
+///             "$" => write!(f, "${}.{:02}", self.0 / 100, self.0 % 100)?,
+///             spec => return Err(errors::Error::InvalidSpec(spec.to_string())),
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let price = Money(1250);
+/// assert_eq!(rt_format!("{:$}", &price).unwrap(), "$12.50");
+/// ```
+///
+/// Errors returned from [`Format::format`] fail the whole format call.
+/// Values are borrowed as `&(dyn Format + Sync)`, so that [`Value`] stays
+/// `Send` and `Sync`.
+pub trait Format {
+    fn format(&self, f: &mut Formatter<'_>) -> Result<(), errors::Error>;
+}
+
+/// The output sink and spec passed to [`Format::format`].
+pub struct Formatter<'a> {
+    raw_spec: &'a str,
+    spec: Option<FormatSpec>,
+    out: &'a mut String,
+}
+
+impl<'a> Formatter<'a> {
+    pub(crate) fn new(raw_spec: &'a str, out: &'a mut String) -> Self {
+        Self {
+            raw_spec,
+            spec: FormatSpec::parse(raw_spec).ok(),
+            out,
+        }
+    }
+
+    /// The text between the `:` and the closing `}` of the replacement field.
+    pub fn raw_spec(&self) -> &str {
+        self.raw_spec
+    }
+
+    /// The standard fields of the spec, or `None` if the spec isn't in fmt's
+    /// standard format spec mini-language.
+    pub fn spec(&self) -> Option<&FormatSpec> {
+        self.spec.as_ref()
+    }
+}
+
+impl fmt::Write for Formatter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.push_str(s);
+        Ok(())
+    }
+}
+
+impl<'s> From<&'s (dyn Format + Sync)> for Value<'s> {
+    fn from(value: &'s (dyn Format + Sync)) -> Self {
+        Value::Custom(value)
+    }
+}
+
+impl<'s, T: Format + Sync> From<&'s T> for Value<'s> {
+    fn from(value: &'s T) -> Self {
+        Value::Custom(value)
+    }
+}
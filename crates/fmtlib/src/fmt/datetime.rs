@@ -0,0 +1,173 @@
+use std::borrow::Cow;
+
+use super::Value;
+
+/// A point in time along with the UTC offset and time zone name it's
+/// displayed in.
+///
+/// Formatted with fmt's strftime-style chrono specs, e.g.
+/// `{:%Y-%m-%d %H:%M %Z}`. Fields are rendered in the value's own offset, so
+/// `%z` and `%Z` print [`DateTime::offset`] and [`DateTime::zone`] rather than
+/// the host's local time zone.
+///
+/// Values converted from `chrono` and `time` types have no zone abbreviation,
+/// so `%Z` prints the offset's own display instead: `UTC` for
+/// `chrono::Utc`, and `+01:00` or `-05:30` for other chrono offsets and for
+/// `time::OffsetDateTime`. Naive and primitive date/times and dates are taken
+/// to be in UTC and `%Z` prints an empty string for them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DateTime<'s> {
+    /// Seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Offset from UTC in seconds, positive east of Greenwich.
+    pub offset: i32,
+    /// Time zone name or abbreviation printed by `%Z`. Empty for date/times
+    /// that don't have a time zone.
+    pub zone: Cow<'s, str>,
+}
+
+impl<'s> DateTime<'s> {
+    pub fn new<Z: Into<Cow<'s, str>>>(timestamp: i64, offset: i32, zone: Z) -> Self {
+        Self {
+            timestamp,
+            offset,
+            zone: zone.into(),
+        }
+    }
+}
+
+impl<'s> From<DateTime<'s>> for Value<'s> {
+    fn from(value: DateTime<'s>) -> Self {
+        Value::DateTime(value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use chrono::{Offset, TimeZone};
+
+    use super::{DateTime, Value};
+
+    impl<'s, Tz> From<chrono::DateTime<Tz>> for Value<'s>
+    where
+        Tz: TimeZone,
+        Tz::Offset: std::fmt::Display,
+    {
+        fn from(value: chrono::DateTime<Tz>) -> Self {
+            let offset = value.offset();
+            DateTime::new(
+                value.timestamp(),
+                offset.fix().local_minus_utc(),
+                offset.to_string(),
+            )
+            .into()
+        }
+    }
+
+    impl<'s> From<chrono::NaiveDateTime> for Value<'s> {
+        fn from(value: chrono::NaiveDateTime) -> Self {
+            DateTime::new(chrono::Utc.from_utc_datetime(&value).timestamp(), 0, "").into()
+        }
+    }
+
+    impl<'s> From<chrono::NaiveDate> for Value<'s> {
+        fn from(value: chrono::NaiveDate) -> Self {
+            value
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight is a valid time")
+                .into()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+        use crate::fmt::rt_format;
+
+        #[test]
+        fn test_from_chrono() {
+            let t = Utc.with_ymd_and_hms(2023, 11, 14, 22, 13, 20).unwrap();
+            let x = rt_format!("{:%Y-%m-%d %H:%M:%S %z %Z}", t);
+            assert_eq!(
+                x.expect("formatting failed"),
+                "2023-11-14 22:13:20 +0000 UTC"
+            );
+
+            let offset = FixedOffset::east_opt(3600).unwrap();
+            let x = rt_format!("{:%H:%M %z %Z}", t.with_timezone(&offset));
+            assert_eq!(x.expect("formatting failed"), "23:13 +0100 +01:00");
+            let offset = FixedOffset::west_opt(5 * 3600 + 1800).unwrap();
+            let x = rt_format!("{:%H:%M %z %Z}", t.with_timezone(&offset));
+            assert_eq!(x.expect("formatting failed"), "16:43 -0530 -05:30");
+
+            let x = rt_format!("{:%Y-%m-%d %H:%M:%S %z|%Z|}", t.naive_utc());
+            assert_eq!(x.expect("formatting failed"), "2023-11-14 22:13:20 +0000||");
+            let date = NaiveDate::from_ymd_opt(2023, 11, 14).unwrap();
+            let x = rt_format!("{:%Y-%m-%d %H:%M %z|%Z|}", date);
+            assert_eq!(x.expect("formatting failed"), "2023-11-14 00:00 +0000||");
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use super::{DateTime, Value};
+
+    impl<'s> From<time::OffsetDateTime> for Value<'s> {
+        fn from(value: time::OffsetDateTime) -> Self {
+            // The time crate doesn't know zone names, so use the offset.
+            let (h, m, _) = value.offset().as_hms();
+            let sign = if value.offset().is_negative() {
+                '-'
+            } else {
+                '+'
+            };
+            let zone = format!("{sign}{:02}:{:02}", h.unsigned_abs(), m.unsigned_abs());
+            DateTime::new(value.unix_timestamp(), value.offset().whole_seconds(), zone).into()
+        }
+    }
+
+    impl<'s> From<time::PrimitiveDateTime> for Value<'s> {
+        fn from(value: time::PrimitiveDateTime) -> Self {
+            DateTime::new(value.assume_utc().unix_timestamp(), 0, "").into()
+        }
+    }
+
+    impl<'s> From<time::Date> for Value<'s> {
+        fn from(value: time::Date) -> Self {
+            value.midnight().into()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use time::{Date, Month, OffsetDateTime, UtcOffset};
+
+        use crate::fmt::rt_format;
+
+        #[test]
+        fn test_from_time() {
+            let t = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+            let x = rt_format!("{:%Y-%m-%d %H:%M:%S %z %Z}", t);
+            assert_eq!(
+                x.expect("formatting failed"),
+                "2023-11-14 22:13:20 +0000 +00:00"
+            );
+
+            let offset = UtcOffset::from_hms(1, 0, 0).unwrap();
+            let x = rt_format!("{:%H:%M %z %Z}", t.to_offset(offset));
+            assert_eq!(x.expect("formatting failed"), "23:13 +0100 +01:00");
+            let offset = UtcOffset::from_hms(-5, -30, 0).unwrap();
+            let x = rt_format!("{:%H:%M %z %Z}", t.to_offset(offset));
+            assert_eq!(x.expect("formatting failed"), "16:43 -0530 -05:30");
+
+            let primitive = t.date().with_time(t.time());
+            let x = rt_format!("{:%Y-%m-%d %H:%M:%S %z|%Z|}", primitive);
+            assert_eq!(x.expect("formatting failed"), "2023-11-14 22:13:20 +0000||");
+            let date = Date::from_calendar_date(2023, Month::November, 14).unwrap();
+            let x = rt_format!("{:%Y-%m-%d %H:%M %z|%Z|}", date);
+            assert_eq!(x.expect("formatting failed"), "2023-11-14 00:00 +0000||");
+        }
+    }
+}
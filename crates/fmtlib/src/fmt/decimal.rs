@@ -0,0 +1,288 @@
+use super::{errors, Align, Count, FormatSpec, Sign};
+
+/// The parts of a C++ `std::numpunct` facet used by `L` specs.
+pub(crate) struct NumPunct<'a> {
+    /// Sizes of the digit groups, starting from the decimal point. The last
+    /// size repeats, and a size of 0 or `CHAR_MAX` ends grouping.
+    pub grouping: &'a [u8],
+    pub thousands_sep: &'a str,
+    pub decimal_point: &'a str,
+}
+
+/// Formats an exact decimal string, e.g. `-1234.5`, with fmt's numeric spec
+/// semantics.
+///
+/// Supported presentation types are none, `d` for integral values and `f`
+/// or `F`. A precision rounds the fractional digits half to even, as fmt
+/// does for exact ties, and requires `f` or `F`: without a type, fmt would
+/// use the general format, which isn't supported.
+pub(crate) fn format_decimal(
+    value: &str,
+    spec: &str,
+    punct: &NumPunct<'_>,
+) -> Result<String, errors::Error> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(int) || (digits.contains('.') && !is_digits(frac)) {
+        return Err(errors::Error::InvalidOperation("malformed decimal string"));
+    }
+
+    let spec = FormatSpec::parse(spec)?;
+    let width = fixed_count(&spec.width)?;
+    let precision = fixed_count(&spec.precision)?;
+    match spec.ty {
+        None if precision.is_some() => {
+            return Err(errors::Error::InvalidSpec(
+                "precision requires 'f' or 'F' for decimal values".into(),
+            ))
+        }
+        None | Some('f') | Some('F') => {}
+        Some('d') if precision.is_some() => {
+            return Err(errors::Error::InvalidSpec(
+                "precision not allowed for integral values".into(),
+            ))
+        }
+        Some('d') if frac.trim_end_matches('0').is_empty() => {}
+        Some('d') => {
+            return Err(errors::Error::InvalidSpec(
+                "'d' requires an integral value".into(),
+            ))
+        }
+        Some(ty) => {
+            return Err(errors::Error::InvalidSpec(format!(
+                "invalid type '{ty}' for a decimal value"
+            )))
+        }
+    }
+
+    let int = int.trim_start_matches('0');
+    let mut digits: Vec<u8> = int.bytes().chain(frac.bytes()).collect();
+    let mut frac_len = frac.len();
+    if spec.ty == Some('d') {
+        digits.truncate(int.len());
+        frac_len = 0;
+    }
+    if let Some(precision) = precision {
+        if frac_len > precision {
+            round_half_even(&mut digits, frac_len - precision);
+            frac_len = precision;
+        } else {
+            digits.resize(digits.len() + precision - frac_len, b'0');
+            frac_len = precision;
+        }
+    }
+    // Pad to at least "0" before the decimal point.
+    while digits.len() <= frac_len {
+        digits.insert(0, b'0');
+    }
+    let (int, frac) = digits.split_at(digits.len() - frac_len);
+    // Only ASCII digits were pushed.
+    let int = std::str::from_utf8(int).expect("digits are ascii");
+    let frac = std::str::from_utf8(frac).expect("digits are ascii");
+
+    let mut body = if spec.localized {
+        group_digits(int, punct.grouping, punct.thousands_sep)
+    } else {
+        int.to_string()
+    };
+    let decimal_point = if spec.localized {
+        punct.decimal_point
+    } else {
+        "."
+    };
+    if !frac.is_empty() || (spec.alternate && precision == Some(0)) {
+        body.push_str(decimal_point);
+        body.push_str(frac);
+    }
+
+    let sign = match (negative, spec.sign) {
+        (true, _) => "-",
+        (false, Some(Sign::Plus)) => "+",
+        (false, Some(Sign::Space)) => " ",
+        (false, _) => "",
+    };
+
+    let len = sign.len() + body.chars().count();
+    let padding = width.map_or(0, |w| w.saturating_sub(len));
+    let mut out = String::with_capacity(len + padding);
+    if spec.zero_pad && spec.align.is_none() {
+        out.push_str(sign);
+        push_repeated(&mut out, '0', padding);
+        out.push_str(&body);
+        return Ok(out);
+    }
+
+    let (before, after) = match spec.align.unwrap_or(Align::Right) {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = spec.fill.unwrap_or(' ');
+    push_repeated(&mut out, fill, before);
+    out.push_str(sign);
+    out.push_str(&body);
+    push_repeated(&mut out, fill, after);
+    Ok(out)
+}
+
+fn push_repeated(out: &mut String, c: char, n: usize) {
+    for _ in 0..n {
+        out.push(c);
+    }
+}
+
+fn fixed_count(count: &Option<Count>) -> Result<Option<usize>, errors::Error> {
+    match count {
+        None => Ok(None),
+        Some(Count::Value(v)) => Ok(Some(*v)),
+        Some(Count::Arg(_)) => Err(errors::Error::InvalidSpec(
+            "dynamic width and precision are not supported for decimal values".into(),
+        )),
+    }
+}
+
+/// Drops the last `n` digits, rounding the remaining ones half to even.
+fn round_half_even(digits: &mut Vec<u8>, n: usize) {
+    let dropped = digits.split_off(digits.len() - n);
+    let odd = digits.last().is_some_and(|d| (d - b'0') % 2 == 1);
+    let round_up = match dropped[0] {
+        b'6'..=b'9' => true,
+        b'5' => odd || dropped[1..].iter().any(|&d| d != b'0'),
+        _ => false,
+    };
+    if !round_up {
+        return;
+    }
+    for d in digits.iter_mut().rev() {
+        if *d == b'9' {
+            *d = b'0';
+        } else {
+            *d += 1;
+            return;
+        }
+    }
+    digits.insert(0, b'1');
+}
+
+fn group_digits(int: &str, grouping: &[u8], sep: &str) -> String {
+    let mut groups = Vec::new();
+    let mut end = int.len();
+    for i in 0.. {
+        let size = match grouping.get(i).or(grouping.last()) {
+            Some(&size) if size > 0 && size < 127 => usize::from(size),
+            _ => break,
+        };
+        if end <= size {
+            break;
+        }
+        groups.push(&int[end - size..end]);
+        end -= size;
+    }
+    groups.push(&int[..end]);
+    groups.reverse();
+    groups.join(sep)
+}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_impls {
+    use super::super::Value;
+
+    impl<'s> From<rust_decimal::Decimal> for Value<'s> {
+        fn from(value: rust_decimal::Decimal) -> Self {
+            Value::Decimal(value.to_string().into())
+        }
+    }
+
+    impl<'s> From<&rust_decimal::Decimal> for Value<'s> {
+        fn from(value: &rust_decimal::Decimal) -> Self {
+            Value::Decimal(value.to_string().into())
+        }
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+mod num_bigint_impls {
+    use super::super::Value;
+
+    macro_rules! impl_from_bigint {
+        ($ty:ty) => {
+            impl<'s> From<$ty> for Value<'s> {
+                fn from(value: $ty) -> Self {
+                    Value::Decimal(value.to_string().into())
+                }
+            }
+
+            impl<'s> From<&$ty> for Value<'s> {
+                fn from(value: &$ty) -> Self {
+                    Value::Decimal(value.to_string().into())
+                }
+            }
+        };
+    }
+
+    impl_from_bigint!(num_bigint::BigInt);
+    impl_from_bigint!(num_bigint::BigUint);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const C: NumPunct<'static> = NumPunct {
+        grouping: &[],
+        thousands_sep: ",",
+        decimal_point: ".",
+    };
+
+    fn fmt(value: &str, spec: &str) -> String {
+        format_decimal(value, spec, &C).unwrap()
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(fmt("-1234.50", ""), "-1234.50");
+        assert_eq!(fmt("007", ""), "7");
+        assert_eq!(fmt("0.125", ".2f"), "0.12");
+        assert_eq!(fmt("0.135", ".2f"), "0.14");
+        assert_eq!(fmt("0.1251", ".2f"), "0.13");
+        assert_eq!(fmt("9.99", ".1f"), "10.0");
+        assert_eq!(fmt("0.5", ".0f"), "0");
+        assert_eq!(fmt("1.5", "#.0f"), "2.");
+        assert_eq!(fmt("12", ".3f"), "12.000");
+        assert_eq!(fmt("12.00", "d"), "12");
+        assert_eq!(fmt("12.5", "+010.2f"), "+000012.50");
+        assert_eq!(fmt("-12.5", "*^9"), "**-12.5**");
+        assert_eq!(fmt("12.5", "<6"), "12.5  ");
+        assert_eq!(
+            fmt("12345678901234567890.5", " "),
+            " 12345678901234567890.5"
+        );
+
+        assert!(format_decimal("12.5", "d", &C).is_err());
+        assert!(format_decimal("1234.50", ".2", &C).is_err());
+        assert!(format_decimal("12.5", "x", &C).is_err());
+        assert!(format_decimal("12.5", "{}", &C).is_err());
+        assert!(format_decimal("1e5", "", &C).is_err());
+        assert!(format_decimal("12.", "", &C).is_err());
+    }
+
+    #[test]
+    fn test_format_decimal_localized() {
+        let punct = NumPunct {
+            grouping: &[3, 2],
+            thousands_sep: ".",
+            decimal_point: ",",
+        };
+        let fmt = |value, spec| format_decimal(value, spec, &punct).unwrap();
+        assert_eq!(fmt("-1234567.5", "L"), "-12.34.567,5");
+        assert_eq!(fmt("123", "L"), "123");
+        assert_eq!(fmt("1234567.5", ""), "1234567.5");
+        assert_eq!(fmt("1234", "L"), "1.234");
+        assert_eq!(group_digits("1234567", &[3], ","), "1,234,567");
+        assert_eq!(group_digits("1234567", &[3, 127], ","), "1234,567");
+    }
+}
@@ -1,11 +1,139 @@
+use std::ops::Range;
+
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+use super::ArgRef;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
 pub enum Error {
     #[error("invalid operation: {0}")]
     InvalidOperation(&'static str),
     #[error("formatting failed: {0}")]
-    FormatFailed(String),
+    FormatFailed(FormatError),
     #[error("incorrect value type access: value does not hold the type {0}")]
     ValueAccess(&'static str),
+    #[error("value out of range: {0}")]
+    OutOfRange(&'static str),
+    #[error("invalid format spec: {0}")]
+    InvalidSpec(String),
+    #[error("invalid format string: {0}")]
+    InvalidTemplate(FormatError),
+    #[error("invalid argument name: {0:?}")]
+    InvalidArgName(String),
+    #[error("failed to write formatted output")]
+    Write(#[from] std::fmt::Error),
+    /// An I/O error, kept as its kind and message so that errors can be
+    /// cloned and compared.
+    #[error("failed to write formatted output: {message}")]
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// The kind of a [`FormatError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FormatErrorKind {
+    /// A `{` that isn't closed, or a `}` that isn't escaped as `}}`.
+    UnmatchedBrace,
+    /// An argument id that's neither an index nor a name.
+    InvalidArgId,
+    /// `{}` mixed with explicit indices, e.g. `{} {0}`.
+    MixedIndexing,
+    /// A field refers to an argument that isn't passed.
+    MissingArgument,
+    /// A spec that no kind of argument accepts, e.g. `{:>>>}`.
+    InvalidSpec,
+    /// A spec that doesn't suit its argument but suits other kinds, e.g.
+    /// `{:x}` with a string, or a width read from an argument that isn't an
+    /// integer.
+    TypeMismatch,
+    /// An argument no field refers to. Only reported by
+    /// [`validate`](super::validate).
+    UnusedArgument,
+    /// Any other failure, e.g. from a custom formatter.
+    Other,
+}
+
+/// An error in a format string, or in formatting it with some arguments.
+///
+/// ```
+/// # use fmtlib::fmt::{errors::{Error, FormatErrorKind}, rt_format};
+/// let Err(Error::FormatFailed(e)) = rt_format!("{} {:x}", 1, "a") else { panic!() };
+/// assert_eq!(e.kind, FormatErrorKind::TypeMismatch);
+/// assert_eq!(e.span, Some(5..6));
+/// ```
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("{message}")]
+pub struct FormatError {
+    pub kind: FormatErrorKind,
+    pub message: String,
+    /// The byte range in the format string the error is about, if known.
+    pub span: Option<Range<usize>>,
+    /// The argument the error is about, with `{}` resolved to its index
+    /// unless the error is in resolving it.
+    pub arg: Option<ArgRef>,
+}
+
+impl FormatError {
+    pub(crate) fn new(kind: FormatErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span: None,
+            arg: None,
+        }
+    }
+
+    pub(crate) fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub(crate) fn with_arg(mut self, arg: ArgRef) -> Self {
+        self.arg = Some(arg);
+        self
+    }
+
+    /// Renders the message, followed by the line of `template` the error is
+    /// in, with carets under its span:
+    ///
+    /// ```text
+    /// argument 2 not found
+    /// {0} {2}
+    ///     ^^^
+    /// ```
+    ///
+    /// Returns just the message if the span is unknown or isn't in
+    /// `template`.
+    pub fn diagnostic(&self, template: &str) -> String {
+        let span = match &self.span {
+            Some(span) if template.get(span.clone()).is_some() => span,
+            _ => return self.message.clone(),
+        };
+        let start = span.start;
+        let line_start = template[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = template[start..]
+            .find('\n')
+            .map_or(template.len(), |i| start + i);
+        let end = span.end.min(line_end);
+        let indent = template[line_start..start].chars().count();
+        let width = template[start..end].chars().count().max(1);
+        format!(
+            "{}\n{}\n{}{}",
+            self.message,
+            &template[line_start..line_end],
+            " ".repeat(indent),
+            "^".repeat(width)
+        )
+    }
 }
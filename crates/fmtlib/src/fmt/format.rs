@@ -0,0 +1,99 @@
+use super::{errors, format_failed, validate_names, Arg};
+
+/// Formats `fmt` with `args`. The format string uses
+/// [fmtlib](https://fmt.dev)'s syntax and is passed to fmt with its length,
+/// so it may contain NUL bytes.
+///
+/// ```
+/// # use fmtlib::fmt::{format, Arg};
+/// let args = [Arg::positional(12), Arg::named("unit", "kg")];
+/// assert_eq!(format("{} {unit}", &args).unwrap(), "12 kg");
+/// ```
+///
+/// Fails with [`errors::Error::InvalidArgName`] if a named argument's name
+/// isn't an identifier.
+///
+/// Format strings are parsed on every call, unless the template cache is
+/// enabled with [`set_template_cache_capacity`](super::set_template_cache_capacity).
+pub fn format(fmt: &str, args: &[Arg]) -> Result<String, errors::Error> {
+    validate_names(args)?;
+    if let Some(template) = super::cached_template(fmt) {
+        return template.render(args);
+    }
+    crate::ffi::fmt::format(fmt, args).map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+/// Returns the size in bytes of the output of [`format`] with the same
+/// arguments, without allocating it.
+pub fn formatted_size(fmt: &str, args: &[Arg]) -> Result<usize, errors::Error> {
+    validate_names(args)?;
+    crate::ffi::fmt::formatted_size(fmt, args).map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+/// Returns the number of terminal columns the output of [`format`] with the
+/// same arguments takes, without allocating it.
+///
+/// ```
+/// # use fmtlib::fmt::{formatted_size, formatted_width, rt_format_args, Arg};
+/// let args = rt_format_args!("日本");
+/// assert_eq!(formatted_size("[{}]", &args).unwrap(), 8);
+/// assert_eq!(formatted_width("[{}]", &args).unwrap(), 6);
+/// ```
+///
+/// The width is estimated per code point, as fmt does for width and precision:
+/// East Asian wide characters and emoji take two columns, everything else one.
+pub fn formatted_width(fmt: &str, args: &[Arg]) -> Result<usize, errors::Error> {
+    validate_names(args)?;
+    crate::ffi::fmt::formatted_width(fmt, args).map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fmt::{rt_format, rt_format_args};
+
+    #[test]
+    fn test_format_errors() {
+        use crate::fmt::{errors::Error, format, Arg};
+
+        let x = format("{}\0{}", &[Arg::positional(1), Arg::positional(2)]);
+        assert_eq!(x.expect("formatting failed"), "1\u{0}2");
+        let name = String::from("name");
+        let x = format("{name}", &[Arg::named(name, 1)]);
+        assert_eq!(x.expect("formatting failed"), "1");
+
+        for name in ["", "a\0b", "1a", "a-b"] {
+            let x = format("{}", &[Arg::named(name, 1)]);
+            assert!(matches!(x, Err(Error::InvalidArgName(n)) if n == name));
+        }
+        let x = rt_format!("{} {}", 1);
+        assert!(matches!(x, Err(Error::FormatFailed(_))));
+        assert_eq!(x, rt_format!("{} {}", 1));
+    }
+
+    #[test]
+    fn test_formatted_size() {
+        use crate::fmt::{formatted_size, formatted_width, Arg};
+
+        let args = rt_format_args!("héllo", "日本", 7);
+        assert_eq!(
+            formatted_size("{}|{}|{:>4}", &args).expect("formatting failed"),
+            18
+        );
+        assert_eq!(
+            formatted_width("{}|{}|{:>4}", &args).expect("formatting failed"),
+            15
+        );
+        // Wide chars split across the chunks output is counted in.
+        let long = "日é".repeat(1000);
+        let args = [Arg::positional(long.as_str())];
+        assert_eq!(
+            formatted_size("{}", &args).expect("formatting failed"),
+            5000
+        );
+        assert_eq!(
+            formatted_width("{}", &args).expect("formatting failed"),
+            3000
+        );
+        assert!(formatted_size("{:d}", &rt_format_args!("a")).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use super::{errors, format_failed, validate_names, Arg};
+
+/// Number formatting conventions used by `L` specs, e.g. `{:L}` or
+/// `{:.2Lf}`. Mirrors C++'s `std::numpunct` facet, but doesn't depend on
+/// the locales installed on the system.
+///
+/// ```
+/// # use fmtlib::fmt::{format_with_locale, rt_format_args, Arg, Locale};
+/// let de = Locale::new('.', ',');
+/// let text = format_with_locale(&de, "{:L} {:.2Lf}", &rt_format_args!(1234567, 1234.5));
+/// assert_eq!(text.unwrap(), "1.234.567 1.234,50");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale {
+    /// Sizes of the digit groups, starting from the decimal point, e.g.
+    /// `[3, 2]` for `12,34,567`. The last size repeats, and a size of 0 or
+    /// 127 and above ends grouping. Empty disables grouping.
+    pub grouping: Vec<u8>,
+    pub thousands_sep: char,
+    pub decimal_point: char,
+}
+
+impl Locale {
+    /// Creates a locale grouping digits by thousands.
+    pub fn new(thousands_sep: char, decimal_point: char) -> Self {
+        Self {
+            grouping: vec![3],
+            thousands_sep,
+            decimal_point,
+        }
+    }
+
+    pub(crate) fn get_grouping(&self) -> &[u8] {
+        &self.grouping
+    }
+
+    // cxx has no char type, so chars cross the bridge as their code point.
+
+    pub(crate) fn get_thousands_sep(&self) -> u32 {
+        self.thousands_sep.into()
+    }
+
+    pub(crate) fn get_decimal_point(&self) -> u32 {
+        self.decimal_point.into()
+    }
+}
+
+/// The "C" locale fmt uses by default: no grouping and `.` as the decimal
+/// point.
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            grouping: Vec::new(),
+            thousands_sep: ',',
+            decimal_point: '.',
+        }
+    }
+}
+
+/// Formats `fmt` with `args`, applying `locale` to `L` specs.
+///
+/// Integers and [`Value::Decimal`](super::Value::Decimal)s support any
+/// separators. Floats are formatted through `std::numpunct<char>`, so they
+/// are only grouped if the thousands separator is ASCII and fall back to
+/// `.` for a non-ASCII decimal point.
+pub fn format_with_locale(
+    locale: &Locale,
+    fmt: &str,
+    args: &[Arg],
+) -> Result<String, errors::Error> {
+    validate_names(args)?;
+    crate::ffi::fmt::format_with_locale(locale, fmt, args)
+        .map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fmt::rt_format_args;
+
+    #[test]
+    fn test_format_with_locale() {
+        use crate::fmt::{format_with_locale, Arg, Locale, Value};
+
+        let de = Locale::new('.', ',');
+        let x = format_with_locale(
+            &de,
+            "{:L} {:.2Lf} {}",
+            &rt_format_args!(1234567, 1234.5, 1234567),
+        );
+        assert_eq!(x.expect("formatting failed"), "1.234.567 1.234,50 1234567");
+        let x = format_with_locale(
+            &de,
+            "{:L}",
+            &rt_format_args!(Value::Decimal("-1234.5".into())),
+        );
+        assert_eq!(x.expect("formatting failed"), "-1.234,5");
+
+        let indian = Locale {
+            grouping: vec![3, 2],
+            ..Locale::new(',', '.')
+        };
+        let x = format_with_locale(&indian, "{:L}", &rt_format_args!(123456789));
+        assert_eq!(x.expect("formatting failed"), "12,34,56,789");
+
+        let fr = Locale::new('\u{202f}', ',');
+        let x = format_with_locale(&fr, "{:L}", &rt_format_args!(-1234567));
+        assert_eq!(x.expect("formatting failed"), "-1\u{202f}234\u{202f}567");
+
+        let x = format_with_locale(&Locale::default(), "{:L}", &rt_format_args!(1234567));
+        assert_eq!(x.expect("formatting failed"), "1234567");
+    }
+}
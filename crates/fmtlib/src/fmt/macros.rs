@@ -45,10 +45,32 @@
 /// assert_eq!(result.unwrap(), "12 12 20");
 /// ```
 ///
+/// ```
+/// # use fmtlib::fmt::{rt_format, Arg};
+///
+/// // A `Vec`, slice or array of values is a single sequence argument. To
+/// // spread values into positional arguments, pass a `Vec` of `Arg`s.
+/// let result = rt_format!("{}", vec![1, 2]);
+/// assert_eq!(result.unwrap(), "[1, 2]");
+///
+/// let args: Vec<Arg> = vec![1, 2].into_iter().map(Arg::from).collect();
+/// let result = rt_format!("{} {}", args);
+/// assert_eq!(result.unwrap(), "1 2");
+/// ```
+///
+/// # Errors
+///
+/// Unlike [`std::format!`], the format string is only checked when it's
+/// formatted, so errors are returned rather than reported at compile time.
+/// See [`format`](crate::fmt::format), which this macro calls.
+///
 /// # Performance
 ///
-/// This macro allocates a new string when constructing the format string. The underlying library
-/// also allocates the format arguments.
+/// The underlying library allocates the format arguments, and the format
+/// string is parsed on every call. Enable the template cache with
+/// [`set_template_cache_capacity`](crate::fmt::set_template_cache_capacity)
+/// to parse repeated format strings once, or use a
+/// [`Template`](crate::fmt::Template).
 ///
 /// # Known issues
 ///
@@ -73,26 +95,230 @@
 macro_rules! rt_format {
     ($msg:literal) => {Ok::<_, $crate::fmt::errors::Error>($msg.to_string())};
     ($msg:expr) => {Ok::<_, $crate::fmt::errors::Error>($msg.to_string())};
-    ($msg:expr, $args:expr) => {unsafe {
+    ($msg:expr, $args:expr) => {{
         use $crate::fmt::IntoArgs;
-        let msg = std::ffi::CString::new($msg).expect("cannot construct format string. invalid byte source");
+        let msg: &str = &$msg;
         let args = $args.into_args();
-        $crate::ffi::fmt::format(msg.as_ptr(), args.as_slice()).map_err(|e| $crate::fmt::errors::Error::FormatFailed(std::format!("{}", e)))
+        $crate::fmt::format(msg, args.as_slice())
     }};
-    ($msg:tt, $($args:tt)+) => {unsafe {
+    ($msg:tt, $($args:tt)+) => {{
         use $crate::fmt::Arg;
-        let msg = std::ffi::CString::new($msg).expect("cannot construct format string. invalid byte source");
-        let args = $crate::fmt::rt_format_args!($($args)+);
-        $crate::ffi::fmt::format(msg.as_ptr(), args.as_slice()).map_err(|e| $crate::fmt::errors::Error::FormatFailed(std::format!("{}", e)))
+        let msg: &str = &$msg;
+        // Matched rather than bound, so the temporaries that `%` and `?`
+        // arguments borrow live until the call returns.
+        match $crate::fmt::rt_format_args!($($args)+) {
+            args => $crate::fmt::format(msg, args.as_slice()),
+        }
     }};
 }
 
 pub use rt_format;
 
+/// Writes a formatted string into a [`std::fmt::Write`] or [`std::io::Write`]
+/// destination, like [`std::write!`]. The format string uses
+/// [fmtlib](https://fmt.dev)'s syntax and the arguments are the same as
+/// [`rt_format!`]'s.
+///
+/// # Examples
+/// ```
+/// # use fmtlib::fmt::rt_write;
+///
+/// let mut s = String::new();
+/// rt_write!(s, "{} {name}", 1, name: "a").unwrap();
+/// assert_eq!(s, "1 a");
+///
+/// let mut bytes = Vec::new();
+/// rt_write!(bytes, "{:>3}", 1).unwrap();
+/// assert_eq!(bytes, b"  1");
+/// ```
+///
+/// Output is written in chunks, see [`format_to`](crate::fmt::format_to) and
+/// [`format_to_io`](crate::fmt::format_to_io).
+#[macro_export]
+macro_rules! rt_write {
+    ($dst:expr, $msg:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::fmt::{FmtWrite as _, IoWrite as _};
+        let msg: &str = &$msg;
+        $dst.rt_write(msg, &[])
+    }};
+    ($dst:expr, $msg:expr, $args:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::fmt::{FmtWrite as _, IntoArgs, IoWrite as _};
+        let msg: &str = &$msg;
+        let args = $args.into_args();
+        $dst.rt_write(msg, args.as_slice())
+    }};
+    ($dst:expr, $msg:tt, $($args:tt)+) => {{
+        #[allow(unused_imports)]
+        use $crate::fmt::{Arg, FmtWrite as _, IoWrite as _};
+        let msg: &str = &$msg;
+        match $crate::fmt::rt_format_args!($($args)+) {
+            args => $dst.rt_write(msg, args.as_slice()),
+        }
+    }};
+}
+
+pub use rt_write;
+
+/// Like [`rt_write!`], with a newline appended to the format string.
+#[macro_export]
+macro_rules! rt_writeln {
+    ($dst:expr) => {
+        $crate::fmt::rt_write!($dst, "\n")
+    };
+    // Literals get the newline at compile time, other strings are copied.
+    ($dst:expr, $msg:literal) => {
+        $crate::fmt::rt_write!($dst, (concat!($msg, "\n")))
+    };
+    ($dst:expr, $msg:literal, $args:expr) => {
+        $crate::fmt::rt_write!($dst, (concat!($msg, "\n")), $args)
+    };
+    ($dst:expr, $msg:literal, $($args:tt)+) => {
+        $crate::fmt::rt_write!($dst, (concat!($msg, "\n")), $($args)+)
+    };
+    ($dst:expr, $msg:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_write!($dst, ([msg, "\n"].concat()))
+    }};
+    ($dst:expr, $msg:expr, $args:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_write!($dst, ([msg, "\n"].concat()), $args)
+    }};
+    ($dst:expr, $msg:tt, $($args:tt)+) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_write!($dst, ([msg, "\n"].concat()), $($args)+)
+    }};
+}
+
+pub use rt_writeln;
+
+/// Prints a formatted string to stdout, like [`std::print!`], without
+/// allocating it. The format string uses [fmtlib](https://fmt.dev)'s syntax
+/// and the arguments are the same as [`rt_format!`]'s.
+///
+/// # Examples
+/// ```
+/// # use fmtlib::fmt::rt_print;
+///
+/// rt_print!("{} {name}\n", 1, name: "a").unwrap();
+/// ```
+///
+/// Unlike [`std::print!`], errors are returned rather than causing a panic.
+/// See [`print`](crate::fmt::print).
+#[macro_export]
+macro_rules! rt_print {
+    ($msg:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::print(msg, &[])
+    }};
+    ($msg:expr, $args:expr) => {{
+        use $crate::fmt::IntoArgs;
+        let msg: &str = &$msg;
+        let args = $args.into_args();
+        $crate::fmt::print(msg, args.as_slice())
+    }};
+    ($msg:tt, $($args:tt)+) => {{
+        use $crate::fmt::Arg;
+        let msg: &str = &$msg;
+        match $crate::fmt::rt_format_args!($($args)+) {
+            args => $crate::fmt::print(msg, args.as_slice()),
+        }
+    }};
+}
+
+pub use rt_print;
+
+/// Like [`rt_print!`], with a newline appended to the format string.
+#[macro_export]
+macro_rules! rt_println {
+    () => {
+        $crate::fmt::rt_print!("\n")
+    };
+    // Literals get the newline at compile time, other strings are copied.
+    ($msg:literal) => {
+        $crate::fmt::rt_print!((concat!($msg, "\n")))
+    };
+    ($msg:literal, $args:expr) => {
+        $crate::fmt::rt_print!((concat!($msg, "\n")), $args)
+    };
+    ($msg:literal, $($args:tt)+) => {
+        $crate::fmt::rt_print!((concat!($msg, "\n")), $($args)+)
+    };
+    ($msg:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_print!(([msg, "\n"].concat()))
+    }};
+    ($msg:expr, $args:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_print!(([msg, "\n"].concat()), $args)
+    }};
+    ($msg:tt, $($args:tt)+) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_print!(([msg, "\n"].concat()), $($args)+)
+    }};
+}
+
+pub use rt_println;
+
+/// Like [`rt_print!`], printing to stderr.
+#[macro_export]
+macro_rules! rt_eprint {
+    ($msg:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::eprint(msg, &[])
+    }};
+    ($msg:expr, $args:expr) => {{
+        use $crate::fmt::IntoArgs;
+        let msg: &str = &$msg;
+        let args = $args.into_args();
+        $crate::fmt::eprint(msg, args.as_slice())
+    }};
+    ($msg:tt, $($args:tt)+) => {{
+        use $crate::fmt::Arg;
+        let msg: &str = &$msg;
+        match $crate::fmt::rt_format_args!($($args)+) {
+            args => $crate::fmt::eprint(msg, args.as_slice()),
+        }
+    }};
+}
+
+pub use rt_eprint;
+
+/// Like [`rt_eprint!`], with a newline appended to the format string.
+#[macro_export]
+macro_rules! rt_eprintln {
+    () => {
+        $crate::fmt::rt_eprint!("\n")
+    };
+    // Literals get the newline at compile time, other strings are copied.
+    ($msg:literal) => {
+        $crate::fmt::rt_eprint!((concat!($msg, "\n")))
+    };
+    ($msg:literal, $args:expr) => {
+        $crate::fmt::rt_eprint!((concat!($msg, "\n")), $args)
+    };
+    ($msg:literal, $($args:tt)+) => {
+        $crate::fmt::rt_eprint!((concat!($msg, "\n")), $($args)+)
+    };
+    ($msg:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_eprint!(([msg, "\n"].concat()))
+    }};
+    ($msg:expr, $args:expr) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_eprint!(([msg, "\n"].concat()), $args)
+    }};
+    ($msg:tt, $($args:tt)+) => {{
+        let msg: &str = &$msg;
+        $crate::fmt::rt_eprint!(([msg, "\n"].concat()), $($args)+)
+    }};
+}
+
+pub use rt_eprintln;
+
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn test_format() {
         let x = rt_format!("test");
@@ -108,4 +334,251 @@ mod tests {
         let x = rt_format!("{0} {b} {0} {2}", 12, b: 20, 21 );
         assert_eq!(x.expect("formatting failed"), "12 20 12 21");
     }
+
+    #[test]
+    fn test_format_numeric() {
+        let x = rt_format!("{:x}", u64::MAX);
+        assert_eq!(x.expect("formatting failed"), "ffffffffffffffff");
+        let x = rt_format!("{} {}", u8::MAX, -12i8);
+        assert_eq!(x.expect("formatting failed"), "255 -12");
+        let x = rt_format!("{} {}", i128::MIN, u128::MAX);
+        assert_eq!(
+            x.expect("formatting failed"),
+            "-170141183460469231731687303715884105728 340282366920938463463374607431768211455"
+        );
+        let x = rt_format!("{}", 0.1f32);
+        assert_eq!(x.expect("formatting failed"), "0.1");
+    }
+
+    #[test]
+    fn test_format_char() {
+        let x = rt_format!("{:d}", 'A');
+        assert_eq!(x.expect("formatting failed"), "65");
+        let x = rt_format!("{} {:?} {:c}", 'a', 'b', 99);
+        assert_eq!(x.expect("formatting failed"), "a 'b' c");
+        let x = rt_format!("{} {:x} {:*^5}", 'é', 'é', 'é');
+        assert_eq!(x.expect("formatting failed"), "é e9 **é**");
+        // Nested fields are resolved like those of fmt's own formatters.
+        let x = rt_format!("[{:>{}}] [{:>{}}] [{}]", 'é', 5, 'a', 3, 'b');
+        assert_eq!(x.expect("formatting failed"), "[    é] [  a] [b]");
+        let x = rt_format!("[{0:^{1}}] [{0:?}] [{0:>{w}}]", 'é', 3, w: 4);
+        assert_eq!(x.expect("formatting failed"), "[ é ] ['é'] [   é]");
+        assert!(rt_format!("{:>{0}} {}", 'é', 1).is_err());
+        // Debug output escapes like fmt's, and specs are checked the same
+        // way for every char.
+        let x = rt_format!("{:?} {:?} {:>5?}", '\u{200b}', "a\u{200b}", 'é');
+        assert_eq!(
+            x.expect("formatting failed"),
+            "'\\u200b' \"a\\u200b\"   'é'"
+        );
+        assert!(rt_format!("{:s}", 'a').is_err());
+        assert!(rt_format!("{:s}", 'é').is_err());
+        assert!(rt_format!("{:.2}", 'é').is_err());
+    }
+
+    #[test]
+    fn test_format_optional() {
+        use crate::fmt::{NoneStyle, Value};
+
+        let x = rt_format!("[{}] [{:>6}] [{:x}]", None::<i32>, None::<i32>, Some(255));
+        assert_eq!(x.expect("formatting failed"), "[none] [  none] [ff]");
+        let empty = Value::optional(None::<i32>, NoneStyle::Empty);
+        let x = rt_format!("[{}] [{:>6}] [{}]", empty.clone(), empty, None::<i32>);
+        assert_eq!(x.expect("formatting failed"), "[] [      ] [none]");
+        let optional = |v: Option<Value<'static>>| Value::optional(v, NoneStyle::Optional);
+        let x = rt_format!(
+            "[{}] [{:x}] [{}]",
+            optional(None),
+            optional(Some(255.into())),
+            optional(Some("a".into()))
+        );
+        assert_eq!(
+            x.expect("formatting failed"),
+            "[none] [optional(ff)] [optional(\"a\")]"
+        );
+    }
+
+    #[test]
+    fn test_format_seq() {
+        let x = rt_format!("{} {::#x} {:n}", vec![1, 2, 3], [10, 255], &["a", "b"][..]);
+        assert_eq!(
+            x.expect("formatting failed"),
+            "[1, 2, 3] [0xa, 0xff] \"a\", \"b\""
+        );
+        let x = rt_format!("{} {}", vec![vec!['a'], vec![]], [0.5; 2]);
+        assert_eq!(x.expect("formatting failed"), "[['a'], []] [0.5, 0.5]");
+    }
+
+    #[test]
+    fn test_format_map() {
+        use std::collections::{BTreeMap, HashMap};
+
+        use crate::fmt::Value;
+
+        let map = BTreeMap::from([("b", 2), ("a", 1)]);
+        let x = rt_format!("{} {:n}", map.clone(), map);
+        assert_eq!(
+            x.expect("formatting failed"),
+            "{\"a\": 1, \"b\": 2} \"a\": 1, \"b\": 2"
+        );
+        let map = HashMap::from([(3, vec!['c']), (1, vec![]), (2, vec!['a', 'b'])]);
+        let x = rt_format!("{}", Value::sorted_map(map));
+        assert_eq!(
+            x.expect("formatting failed"),
+            "{1: [], 2: ['a', 'b'], 3: ['c']}"
+        );
+    }
+
+    #[test]
+    fn test_format_tuple() {
+        let x = rt_format!("{} {:n}", (1, "a", true), (-1, 'b'));
+        assert_eq!(x.expect("formatting failed"), "(1, \"a\", true) -1'b'");
+        let x = rt_format!("{}", vec![(1.5, None::<i32>)]);
+        assert_eq!(x.expect("formatting failed"), "[(1.5, none)]");
+    }
+
+    #[test]
+    fn test_format_pointer() {
+        let p = 0x1234 as *const u8;
+        let nn = std::ptr::NonNull::new(p as *mut u8).unwrap();
+        let x = rt_format!("{} {:p} {:*>8}", p, p as *mut u8, nn);
+        assert_eq!(x.expect("formatting failed"), "0x1234 0x1234 **0x1234");
+        let x = rt_format!("{:>8p}", p);
+        assert_eq!(x.expect("formatting failed"), "  0x1234");
+        let x = rt_format!("{:d}", p);
+        assert!(x.is_err());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        use crate::fmt::Value;
+
+        let x = rt_format!("{} {::02x} {:x}", b"\x01\xff", b"\xde\xad", b"\xbe\xef");
+        assert_eq!(x.expect("formatting failed"), "[1, 255] [de, ad] beef");
+        let bytes = vec![0xde, 0xad, 0xbe, 0xef];
+        let (a, b) = (Value::bytes(&bytes[..]), Value::bytes(bytes.clone()));
+        let x = rt_format!("[{:>10X}] [{:.4x}]", a, b);
+        assert_eq!(x.expect("formatting failed"), "[  DEADBEEF] [dead]");
+        let x = rt_format!("[{:>12}] [{:*<{}n:02x}]", Value::bytes(b"ab"), b"\x01", 4);
+        assert_eq!(x.expect("formatting failed"), "[    [97, 98]] [01**]");
+    }
+
+    #[test]
+    fn test_format_time() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let d = Duration::from_millis(1500);
+        let x = rt_format!("{} {:%Q%q} {:%H:%M:%S}", d, d, Duration::from_secs(3723));
+        assert_eq!(x.expect("formatting failed"), "1500ms 1500ms 01:02:03");
+        let x = rt_format!("{} {:%S}", Duration::from_secs(5), Duration::from_micros(1));
+        assert_eq!(x.expect("formatting failed"), "5s 00.000001");
+        let t = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let x = rt_format!(
+            "{:%Y-%m-%d %H:%M} {:%Y} {}",
+            t,
+            UNIX_EPOCH - Duration::from_secs(1),
+            UNIX_EPOCH
+        );
+        assert_eq!(
+            x.expect("formatting failed"),
+            "2023-11-14 22:13 1969 1970-01-01 00:00:00"
+        );
+        let x = rt_format!("{:%Y}", UNIX_EPOCH - Duration::from_millis(1500));
+        assert_eq!(x.expect("formatting failed"), "1969");
+        let x = rt_format!("{}", Duration::from_secs(400 * 365 * 86_400));
+        assert_eq!(x.expect("formatting failed"), "12614400000s");
+        let x = rt_format!("{}", Duration::MAX);
+        assert!(x.is_err());
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        use crate::fmt::DateTime;
+
+        let t = DateTime::new(1_700_000_000, 2 * 3600, "CEST");
+        let x = rt_format!("{ts:%Y-%m-%d %H:%M %Z} {ts:%z}", ts: t);
+        assert_eq!(x.expect("formatting failed"), "2023-11-15 00:13 CEST +0200");
+        let t = DateTime::new(1_700_000_000, -(5 * 3600 + 1800), "");
+        let x = rt_format!("{:%H:%M %z}", t);
+        assert_eq!(x.expect("formatting failed"), "16:43 -0530");
+    }
+
+    #[test]
+    fn test_format_custom() {
+        use std::fmt::Write;
+
+        use crate::fmt::{errors, Count, Format, Formatter};
+
+        struct Point(f64, f64);
+
+        impl Format for Point {
+            fn format(&self, f: &mut Formatter<'_>) -> Result<(), errors::Error> {
+                let precision = match f.spec().map(|s| &s.precision) {
+                    Some(Some(Count::Value(p))) => *p,
+                    Some(_) => 1,
+                    None => return Err(errors::Error::InvalidSpec(f.raw_spec().to_string())),
+                };
+                write!(f, "({:.*}, {:.*})", precision, self.0, precision, self.1)?;
+                Ok(())
+            }
+        }
+
+        let p = Point(1.0, -2.34);
+        let x = rt_format!("{} {:.2} {}", &p, &p, vec![&p]);
+        assert_eq!(
+            x.expect("formatting failed"),
+            "(1.0, -2.3) (1.00, -2.34) [(1.0, -2.3)]"
+        );
+        let x = rt_format!("{:%}", &p);
+        assert!(x
+            .unwrap_err()
+            .to_string()
+            .contains("invalid format spec: %"));
+    }
+
+    #[test]
+    fn test_format_display() {
+        use std::net::Ipv4Addr;
+
+        use crate::fmt::{debug, display};
+
+        #[derive(Debug)]
+        struct Point(i32, i32);
+
+        let (addr, p) = (Ipv4Addr::LOCALHOST, Point(1, -2));
+        let x = rt_format!("[{:>11}] [{:.3}]", %addr, %addr);
+        assert_eq!(x.expect("formatting failed"), "[  127.0.0.1] [127]");
+        assert!(rt_format!("{:?}", %"a").is_err());
+        assert!(rt_format!("{:>5?}", %addr).is_err());
+        let x = rt_format!("[{}] [{:?}] [{:*^13}]", ?p, ?p, ?Some(1));
+        assert_eq!(
+            x.expect("formatting failed"),
+            "[Point(1, -2)] [Point(1, -2)] [***Some(1)***]"
+        );
+        let x = rt_format!("{} {a}", vec![debug(&p)], a: display(&addr));
+        assert_eq!(x.expect("formatting failed"), "[Point(1, -2)] 127.0.0.1");
+        // Temporaries live until the output is formatted.
+        let x = rt_format!("{}/{}", %p.0.to_string(), ?p.1.abs());
+        assert_eq!(x.expect("formatting failed"), "1/2");
+    }
+
+    #[test]
+    fn test_format_decimal() {
+        use crate::fmt::Value;
+
+        let d = Value::Decimal("-1234567.125".into());
+        let x = rt_format!("{} {:.2f} {:+015.1f}", d.clone(), d.clone(), d);
+        assert_eq!(
+            x.expect("formatting failed"),
+            "-1234567.125 -1234567.12 -000001234567.1"
+        );
+        let big = Value::Decimal("123456789012345678901234567890".into());
+        let x = rt_format!("[{:>32d}] [{:L}]", big.clone(), big);
+        assert_eq!(
+            x.expect("formatting failed"),
+            "[  123456789012345678901234567890] [123456789012345678901234567890]"
+        );
+        let x = rt_format!("{:x}", Value::Decimal("1.5".into()));
+        assert!(x.is_err());
+    }
 }
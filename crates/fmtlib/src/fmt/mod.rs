@@ -1,10 +1,35 @@
 mod arg;
+mod ast;
+mod cache;
+mod custom;
+mod datetime;
+mod decimal;
 pub mod errors;
+mod format;
+mod locale;
 #[macro_use]
 mod macros;
+mod print;
+mod spec;
+mod style;
+mod template;
+mod validate;
 mod value;
+mod write;
 
 pub use arg::*;
+pub use ast::*;
+pub use cache::*;
+pub use custom::*;
+pub use datetime::*;
 pub use fmtlib_proc_macros::rt_format_args;
-pub use macros::rt_format;
+pub use format::*;
+pub use locale::*;
+pub use macros::{rt_eprint, rt_eprintln, rt_format, rt_print, rt_println, rt_write, rt_writeln};
+pub use print::*;
+pub use spec::*;
+pub use style::*;
+pub use template::*;
+pub use validate::*;
 pub use value::*;
+pub use write::*;
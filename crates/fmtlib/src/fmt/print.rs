@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+
+use super::{errors, format_failed, validate_names, Arg};
+
+/// Formats `fmt` with `args` and prints the output to stdout through fmt's
+/// `vprint`, without allocating it.
+///
+/// Rust's stdout is locked and flushed while printing, so the output is
+/// ordered with, and doesn't interleave with, output from [`println!`].
+pub fn print(fmt: &str, args: &[Arg]) -> Result<(), errors::Error> {
+    validate_names(args)?;
+    let mut stdout = io::stdout().lock();
+    stdout.flush()?;
+    crate::ffi::fmt::print(fmt, args).map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+/// Like [`print`], printing to stderr.
+pub fn eprint(fmt: &str, args: &[Arg]) -> Result<(), errors::Error> {
+    validate_names(args)?;
+    let mut stderr = io::stderr().lock();
+    stderr.flush()?;
+    crate::ffi::fmt::eprint(fmt, args).map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_print() {
+        use crate::fmt::{errors::Error, Arg};
+
+        // Prints nothing, fmt's stdout isn't captured by the test harness.
+        rt_print!("").expect("printing failed");
+        rt_eprint!("{}", "").expect("printing failed");
+        assert!(matches!(
+            rt_print!("{:d}", "a"),
+            Err(Error::FormatFailed(_))
+        ));
+        assert!(matches!(rt_eprintln!("{}"), Err(Error::FormatFailed(_))));
+        let template = String::from("{:d}");
+        assert!(matches!(
+            rt_println!(template, "a"),
+            Err(Error::FormatFailed(_))
+        ));
+        assert!(matches!(
+            rt_println!("{}", vec![Arg::named("", 1)]),
+            Err(Error::InvalidArgName(_))
+        ));
+    }
+}
@@ -0,0 +1,346 @@
+use std::{fmt, str::FromStr};
+
+use super::errors;
+
+/// A reference to a formatting argument.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ArgRef {
+    /// The next argument, e.g. `{}`.
+    Auto,
+    /// A positional argument, e.g. `{0}`.
+    Index(usize),
+    /// A named argument, e.g. `{name}`.
+    Name(String),
+}
+
+impl ArgRef {
+    /// Parses the argument id of a replacement field, e.g. the `name` in
+    /// `{name:>8}`.
+    pub fn parse(id: &str) -> Result<Self, errors::Error> {
+        let mut chars = id.chars();
+        match chars.next() {
+            None => Ok(Self::Auto),
+            Some('0') if id.len() == 1 => Ok(Self::Index(0)),
+            Some('1'..='9') if id.bytes().all(|b| b.is_ascii_digit()) => id
+                .parse()
+                .map(Self::Index)
+                .map_err(|_| errors::Error::InvalidSpec("argument index is too big".into())),
+            Some(c) if c == '_' || c.is_ascii_alphabetic() => {
+                if chars.all(|c| c == '_' || c.is_ascii_alphanumeric()) {
+                    Ok(Self::Name(id.to_string()))
+                } else {
+                    Err(errors::Error::InvalidSpec(format!(
+                        "invalid argument id '{id}'"
+                    )))
+                }
+            }
+            _ => Err(errors::Error::InvalidSpec(format!(
+                "invalid argument id '{id}'"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ArgRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgRef::Auto => Ok(()),
+            ArgRef::Index(i) => write!(f, "{i}"),
+            ArgRef::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// `<`
+    Left,
+    /// `>`
+    Right,
+    /// `^`
+    Center,
+}
+
+impl Align {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '<' => Some(Self::Left),
+            '>' => Some(Self::Right),
+            '^' => Some(Self::Center),
+            _ => None,
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            Self::Left => '<',
+            Self::Right => '>',
+            Self::Center => '^',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
+    /// `+`
+    Plus,
+    /// `-`
+    Minus,
+    /// ` `
+    Space,
+}
+
+impl Sign {
+    fn as_char(self) -> char {
+        match self {
+            Self::Plus => '+',
+            Self::Minus => '-',
+            Self::Space => ' ',
+        }
+    }
+}
+
+/// A width or precision.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Count {
+    /// A literal count, e.g. the `8` in `{:8}`.
+    Value(usize),
+    /// A count read from an argument, e.g. the `{w}` in `{:{w}}`.
+    Arg(ArgRef),
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Count::Value(v) => write!(f, "{v}"),
+            Count::Arg(arg) => write!(f, "{{{arg}}}"),
+        }
+    }
+}
+
+/// The standard fields of a replacement field's spec, following fmt's
+/// format spec mini-language:
+///
+/// ```text
+/// [[fill]align][sign]["#"]["0"][width]["." precision]["L"][type]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormatSpec {
+    pub fill: Option<char>,
+    pub align: Option<Align>,
+    pub sign: Option<Sign>,
+    /// `#`
+    pub alternate: bool,
+    /// `0`
+    pub zero_pad: bool,
+    pub width: Option<Count>,
+    pub precision: Option<Count>,
+    /// `L`
+    pub localized: bool,
+    /// The presentation type, e.g. `x` or `?`.
+    pub ty: Option<char>,
+}
+
+impl FormatSpec {
+    /// Parses a spec, i.e. the text after the `:` of a replacement field.
+    /// Fails for specs that aren't in the standard mini-language, such as
+    /// chrono or range specs.
+    pub fn parse(spec: &str) -> Result<Self, errors::Error> {
+        let mut out = Self::default();
+        let mut rest = spec;
+
+        let mut chars = rest.chars();
+        if let (Some(fill), Some(align)) = (chars.next(), chars.next().and_then(Align::from_char)) {
+            if fill == '{' || fill == '}' {
+                return Err(errors::Error::InvalidSpec("invalid fill character".into()));
+            }
+            out.fill = Some(fill);
+            out.align = Some(align);
+            rest = chars.as_str();
+        } else if let Some(align) = rest.chars().next().and_then(Align::from_char) {
+            out.align = Some(align);
+            rest = &rest[1..];
+        }
+
+        out.sign = match rest.chars().next() {
+            Some('+') => Some(Sign::Plus),
+            Some('-') => Some(Sign::Minus),
+            Some(' ') => Some(Sign::Space),
+            _ => None,
+        };
+        if out.sign.is_some() {
+            rest = &rest[1..];
+        }
+        if let Some(r) = rest.strip_prefix('#') {
+            out.alternate = true;
+            rest = r;
+        }
+        if let Some(r) = rest.strip_prefix('0') {
+            out.zero_pad = true;
+            rest = r;
+        }
+
+        (out.width, rest) = parse_count(rest)?;
+        if let Some(r) = rest.strip_prefix('.') {
+            let (precision, r) = parse_count(r)?;
+            if precision.is_none() {
+                return Err(errors::Error::InvalidSpec(
+                    "missing precision specifier".into(),
+                ));
+            }
+            out.precision = precision;
+            rest = r;
+        }
+        if let Some(r) = rest.strip_prefix('L') {
+            out.localized = true;
+            rest = r;
+        }
+
+        let mut chars = rest.chars();
+        match chars.next() {
+            None => {}
+            Some(ty) if is_type(ty) && chars.as_str().is_empty() => out.ty = Some(ty),
+            Some(_) => {
+                return Err(errors::Error::InvalidSpec(format!(
+                    "invalid format specifier '{spec}'"
+                )))
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+impl FromStr for FormatSpec {
+    type Err = errors::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for FormatSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        if let Some(fill) = self.fill {
+            f.write_char(fill)?;
+        }
+        if let Some(align) = self.align {
+            f.write_char(align.as_char())?;
+        }
+        if let Some(sign) = self.sign {
+            f.write_char(sign.as_char())?;
+        }
+        if self.alternate {
+            f.write_char('#')?;
+        }
+        if self.zero_pad {
+            f.write_char('0')?;
+        }
+        if let Some(width) = &self.width {
+            write!(f, "{width}")?;
+        }
+        if let Some(precision) = &self.precision {
+            write!(f, ".{precision}")?;
+        }
+        if self.localized {
+            f.write_char('L')?;
+        }
+        if let Some(ty) = self.ty {
+            f.write_char(ty)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_type(c: char) -> bool {
+    matches!(
+        c,
+        'a' | 'A'
+            | 'b'
+            | 'B'
+            | 'c'
+            | 'd'
+            | 'e'
+            | 'E'
+            | 'f'
+            | 'F'
+            | 'g'
+            | 'G'
+            | 'o'
+            | 'p'
+            | 's'
+            | 'x'
+            | 'X'
+            | '?'
+    )
+}
+
+fn parse_count(s: &str) -> Result<(Option<Count>, &str), errors::Error> {
+    if let Some(r) = s.strip_prefix('{') {
+        let end = r
+            .find('}')
+            .ok_or_else(|| errors::Error::InvalidSpec("missing '}' in format spec".into()))?;
+        let arg = ArgRef::parse(&r[..end])?;
+        return Ok((Some(Count::Arg(arg)), &r[end + 1..]));
+    }
+
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return Ok((None, s));
+    }
+    let count = s[..digits]
+        .parse()
+        .map_err(|_| errors::Error::InvalidSpec("number is too big".into()))?;
+    Ok((Some(Count::Value(count)), &s[digits..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let spec = FormatSpec::parse("*^+#010.{p}Lx").unwrap();
+        assert_eq!(
+            spec,
+            FormatSpec {
+                fill: Some('*'),
+                align: Some(Align::Center),
+                sign: Some(Sign::Plus),
+                alternate: true,
+                zero_pad: true,
+                width: Some(Count::Value(10)),
+                precision: Some(Count::Arg(ArgRef::Name("p".into()))),
+                localized: true,
+                ty: Some('x'),
+            }
+        );
+        assert_eq!(spec.to_string(), "*^+#010.{p}Lx");
+
+        assert_eq!(FormatSpec::parse("").unwrap(), FormatSpec::default());
+        let spec = FormatSpec::parse("<<{}").unwrap();
+        assert_eq!(spec.fill, Some('<'));
+        assert_eq!(spec.align, Some(Align::Left));
+        assert_eq!(spec.width, Some(Count::Arg(ArgRef::Auto)));
+        let spec = FormatSpec::parse("é>3").unwrap();
+        assert_eq!(spec.fill, Some('é'));
+
+        assert!(FormatSpec::parse(".").is_err());
+        assert!(FormatSpec::parse("%H:%M").is_err());
+        assert!(FormatSpec::parse(":x").is_err());
+        assert!(FormatSpec::parse("xx").is_err());
+    }
+
+    #[test]
+    fn test_parse_arg_ref() {
+        assert_eq!(ArgRef::parse("").unwrap(), ArgRef::Auto);
+        assert_eq!(ArgRef::parse("0").unwrap(), ArgRef::Index(0));
+        assert_eq!(ArgRef::parse("12").unwrap(), ArgRef::Index(12));
+        assert_eq!(ArgRef::parse("_a1").unwrap(), ArgRef::Name("_a1".into()));
+        assert!(ArgRef::parse("01").is_err());
+        assert!(ArgRef::parse("1a").is_err());
+        assert!(ArgRef::parse("a-b").is_err());
+    }
+}
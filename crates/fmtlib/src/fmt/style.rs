@@ -0,0 +1,251 @@
+use std::{
+    io::IsTerminal,
+    ops::BitOr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use super::{errors, format_failed, validate_names, Arg, Value};
+
+/// A foreground or background color. Mirrors fmt's `terminal_color` and
+/// `rgb`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // cxx has no enums with data, so colors cross the bridge as a u32: their
+    // ANSI foreground code for terminal colors, or `0xRRGGBB` with
+    // `RGB_FLAG` set.
+    const RGB_FLAG: u32 = 1 << 24;
+
+    fn encode(self) -> u32 {
+        match self {
+            Self::Black => 30,
+            Self::Red => 31,
+            Self::Green => 32,
+            Self::Yellow => 33,
+            Self::Blue => 34,
+            Self::Magenta => 35,
+            Self::Cyan => 36,
+            Self::White => 37,
+            Self::BrightBlack => 90,
+            Self::BrightRed => 91,
+            Self::BrightGreen => 92,
+            Self::BrightYellow => 93,
+            Self::BrightBlue => 94,
+            Self::BrightMagenta => 95,
+            Self::BrightCyan => 96,
+            Self::BrightWhite => 97,
+            Self::Rgb(r, g, b) => {
+                Self::RGB_FLAG | (u32::from(r) << 16) | (u32::from(g) << 8) | u32::from(b)
+            }
+        }
+    }
+}
+
+/// A set of text emphases. Mirrors fmt's `emphasis`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Emphasis(u8);
+
+impl Emphasis {
+    pub const BOLD: Self = Self(1);
+    pub const FAINT: Self = Self(1 << 1);
+    pub const ITALIC: Self = Self(1 << 2);
+    pub const UNDERLINE: Self = Self(1 << 3);
+    pub const BLINK: Self = Self(1 << 4);
+    pub const REVERSE: Self = Self(1 << 5);
+    pub const CONCEAL: Self = Self(1 << 6);
+    pub const STRIKETHROUGH: Self = Self(1 << 7);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Emphasis {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Text style made of an optional foreground and background color and a set
+/// of emphases. Mirrors fmt's `text_style`, so styles are combined with `|`:
+///
+/// ```
+/// # use fmtlib::fmt::{fg, bg, Color, Emphasis, Style};
+/// let style = fg(Color::Red) | bg(Color::Rgb(0, 0, 0)) | Emphasis::BOLD;
+/// assert_eq!(style.fg, Some(Color::Red));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub emphasis: Emphasis,
+}
+
+/// Creates a style with a foreground color.
+pub fn fg(color: Color) -> Style {
+    Style {
+        fg: Some(color),
+        ..Style::default()
+    }
+}
+
+/// Creates a style with a background color.
+pub fn bg(color: Color) -> Style {
+    Style {
+        bg: Some(color),
+        ..Style::default()
+    }
+}
+
+impl From<Emphasis> for Style {
+    fn from(emphasis: Emphasis) -> Self {
+        Style {
+            emphasis,
+            ..Style::default()
+        }
+    }
+}
+
+/// Colors set on the right-hand side take precedence.
+impl<S: Into<Style>> BitOr<S> for Style {
+    type Output = Style;
+
+    fn bitor(self, rhs: S) -> Style {
+        let rhs = rhs.into();
+        Style {
+            fg: rhs.fg.or(self.fg),
+            bg: rhs.bg.or(self.bg),
+            emphasis: self.emphasis | rhs.emphasis,
+        }
+    }
+}
+
+impl Style {
+    pub(crate) fn get_fg(&self) -> u32 {
+        self.fg.map_or(0, Color::encode)
+    }
+
+    pub(crate) fn get_bg(&self) -> u32 {
+        self.bg.map_or(0, Color::encode)
+    }
+
+    pub(crate) fn get_emphasis(&self) -> u8 {
+        self.emphasis.0
+    }
+}
+
+/// Creates a [`Value::Styled`], which renders `value` with `style` as fmt's
+/// `styled` does. Fill, alignment and width are applied inside the styling.
+pub fn styled<'s, T: Into<Value<'s>>>(value: T, style: Style) -> Value<'s> {
+    Value::Styled(Box::new(value.into()), style)
+}
+
+/// Controls whether styles are rendered as ANSI escape sequences. See
+/// [`set_color_choice`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ColorChoice {
+    /// Styles are rendered unless the `NO_COLOR` environment variable is set
+    /// to a non-empty value or stdout isn't a terminal.
+    #[default]
+    Auto,
+    Always,
+    /// Styles are ignored and no escape sequences are written.
+    Never,
+}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// Sets whether styles are rendered by every subsequent format call.
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Returns the current [`ColorChoice`].
+pub fn color_choice() -> ColorChoice {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Returns whether styles are currently rendered, resolving
+/// [`ColorChoice::Auto`].
+pub fn colors_enabled() -> bool {
+    match color_choice() {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    }
+}
+
+/// Formats `fmt` with `args` and renders the whole message with `style`,
+/// e.g. `format_styled(&fg(Color::Red), "error: {}", &rt_format_args!(msg))`.
+/// The style is dropped when colors are disabled, see [`colors_enabled`].
+pub fn format_styled(style: &Style, fmt: &str, args: &[Arg]) -> Result<String, errors::Error> {
+    validate_names(args)?;
+    let result = if colors_enabled() {
+        crate::ffi::fmt::format_styled(style, fmt, args)
+    } else {
+        crate::ffi::fmt::format(fmt, args)
+    };
+    result.map_err(|e| format_failed(fmt, args, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fmt::rt_format_args;
+
+    #[test]
+    fn test_format_styled() {
+        use crate::fmt::{
+            fg, format_styled, set_color_choice, styled, Arg, Color, ColorChoice, Emphasis,
+        };
+
+        set_color_choice(ColorChoice::Always);
+        let style = fg(Color::Red) | Emphasis::BOLD;
+        let x = format_styled(&style, "{} {}", &rt_format_args!(1, "a"));
+        assert_eq!(x.expect("formatting failed"), "\x1b[1m\x1b[31m1 a\x1b[0m");
+        let x = rt_format!("[{:>3}]", styled(7, fg(Color::Rgb(0, 128, 255))));
+        assert_eq!(
+            x.expect("formatting failed"),
+            "[\x1b[38;2;000;128;255m  7\x1b[0m]"
+        );
+
+        set_color_choice(ColorChoice::Never);
+        let x = format_styled(&style, "{} {}", &rt_format_args!(1, "a"));
+        assert_eq!(x.expect("formatting failed"), "1 a");
+        let x = rt_format!("[{:>3}]", styled(7, fg(Color::Green)));
+        assert_eq!(x.expect("formatting failed"), "[  7]");
+        set_color_choice(ColorChoice::default());
+    }
+}
@@ -0,0 +1,273 @@
+use std::str::FromStr;
+
+use super::{
+    errors::{self, FormatError, FormatErrorKind},
+    format_failed, validate_names, Arg, ArgRef, Ast, Segment,
+};
+
+/// A piece of a parsed [`Template`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Piece {
+    /// Literal text, with `{{` and `}}` unescaped.
+    Literal(String),
+    /// A replacement field, e.g. `{name:>8}`.
+    Field(Field),
+}
+
+/// A replacement field of a [`Template`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The argument the field formats. `{}` is resolved to the index of the
+    /// argument it refers to, so this is never [`ArgRef::Auto`].
+    pub arg: ArgRef,
+    /// The spec as written, i.e. the text after the `:`.
+    pub spec: String,
+}
+
+/// A format string that's parsed and validated once, to be rendered with
+/// different arguments.
+///
+/// ```
+/// # use fmtlib::fmt::{rt_format_args, Arg, Template};
+/// let template = Template::parse("{:>5} {unit}").unwrap();
+/// let text = template.render(&rt_format_args!(12, unit: "kg")).unwrap();
+/// assert_eq!(text, "   12 kg");
+/// ```
+///
+/// Specs are checked against the argument types when the template is
+/// rendered, as fmt does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    pieces: Vec<Piece>,
+    /// Whether a spec reads its width or precision from an argument.
+    dynamic: bool,
+}
+
+impl Template {
+    /// Parses a format string in [fmtlib](https://fmt.dev)'s syntax.
+    pub fn parse(source: &str) -> Result<Self, errors::Error> {
+        let mut pieces = Vec::new();
+        let mut dynamic = false;
+        // The index of the next `{}`, or None once an index is given.
+        let mut next_index = Some(0);
+
+        for segment in Ast::parse(source)?.segments {
+            let piece = match segment {
+                Segment::Literal(literal) => Piece::Literal(literal.text),
+                Segment::Field(field) => {
+                    let arg = resolve(field.arg, &mut next_index)
+                        .map_err(|e| errors::Error::InvalidTemplate(e.with_span(field.span)))?;
+                    let spec = field.spec.unwrap_or_default();
+                    for spec_arg in spec.args {
+                        resolve(spec_arg.arg, &mut next_index).map_err(|e| {
+                            errors::Error::InvalidTemplate(e.with_span(spec_arg.span))
+                        })?;
+                        dynamic = true;
+                    }
+                    Piece::Field(Field {
+                        arg,
+                        spec: spec.text,
+                    })
+                }
+            };
+            pieces.push(piece);
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            pieces,
+            dynamic,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &Field> {
+        self.pieces.iter().filter_map(|piece| match piece {
+            Piece::Field(field) => Some(field),
+            Piece::Literal(_) => None,
+        })
+    }
+
+    /// Formats the template with `args`, without parsing it again.
+    pub fn render(&self, args: &[Arg]) -> Result<String, errors::Error> {
+        validate_names(args)?;
+        if self.dynamic {
+            // fmt reads dynamic widths and precisions while it parses a
+            // spec, so these are only available through the whole format
+            // string.
+            return crate::ffi::fmt::format(&self.source, args)
+                .map_err(|e| format_failed(&self.source, args, e.to_string()));
+        }
+        let fields = self
+            .fields()
+            .map(|field| find_arg(args, &field.arg))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format_failed(&self.source, args, "argument not found".into()))?;
+        crate::ffi::fmt::render_template(self, &fields, args)
+            .map_err(|e| format_failed(&self.source, args, e.to_string()))
+    }
+
+    pub(crate) fn get_piece_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    pub(crate) fn is_field(&self, index: usize) -> bool {
+        matches!(self.pieces[index], Piece::Field(_))
+    }
+
+    /// Returns a literal's text or a field's spec.
+    pub(crate) fn get_text(&self, index: usize) -> &str {
+        match &self.pieces[index] {
+            Piece::Literal(text) => text,
+            Piece::Field(field) => &field.spec,
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = errors::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Resolves `{}` to an index, rejecting a mix of `{}` and explicit indices
+/// like fmt. Names count as explicit, so `{} {name}` is valid but
+/// `{name} {}` isn't.
+pub(crate) fn resolve(arg: ArgRef, next_index: &mut Option<usize>) -> Result<ArgRef, FormatError> {
+    match arg {
+        ArgRef::Auto => {
+            let index = next_index.ok_or_else(|| {
+                FormatError::new(
+                    FormatErrorKind::MixedIndexing,
+                    "cannot switch from manual to automatic argument indexing",
+                )
+                .with_arg(ArgRef::Auto)
+            })?;
+            *next_index = Some(index + 1);
+            Ok(ArgRef::Index(index))
+        }
+        ArgRef::Index(_) => {
+            if next_index.is_some_and(|i| i > 0) {
+                return Err(FormatError::new(
+                    FormatErrorKind::MixedIndexing,
+                    "cannot switch from automatic to manual argument indexing",
+                )
+                .with_arg(arg));
+            }
+            *next_index = None;
+            Ok(arg)
+        }
+        ArgRef::Name(_) => {
+            *next_index = None;
+            Ok(arg)
+        }
+    }
+}
+
+/// Finds the argument a field refers to. Like in fmt, named arguments can
+/// also be referenced by their index.
+fn find_arg(args: &[Arg], arg: &ArgRef) -> Option<usize> {
+    match arg {
+        ArgRef::Index(index) => Some(*index).filter(|&i| i < args.len()),
+        ArgRef::Name(name) => args
+            .iter()
+            .position(|a| matches!(a, Arg::Named(n, _) if n == name)),
+        ArgRef::Auto => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fmt::{rt_format_args, Arg};
+
+    #[test]
+    fn test_template() {
+        use crate::fmt::{errors::Error, ArgRef, Field, Piece, Template};
+
+        let template = Template::parse("{{{}}} {:x}|{name:>4}").expect("parsing failed");
+        assert_eq!(
+            template.pieces(),
+            [
+                Piece::Literal("{".into()),
+                Piece::Field(Field {
+                    arg: ArgRef::Index(0),
+                    spec: "".into()
+                }),
+                Piece::Literal("} ".into()),
+                Piece::Field(Field {
+                    arg: ArgRef::Index(1),
+                    spec: "x".into()
+                }),
+                Piece::Literal("|".into()),
+                Piece::Field(Field {
+                    arg: ArgRef::Name("name".into()),
+                    spec: ">4".into()
+                }),
+            ]
+        );
+        let x = template.render(&rt_format_args!(1, 255, name: "ab"));
+        assert_eq!(x.expect("formatting failed"), "{1} ff|  ab");
+        let x = template.render(&rt_format_args!('é', 0xabu8, name: 'c'));
+        assert_eq!(x.expect("formatting failed"), "{é} ab|   c");
+        assert!(matches!(
+            template.render(&rt_format_args!(1, 2)),
+            Err(Error::FormatFailed(_))
+        ));
+        assert!(matches!(
+            template.render(&rt_format_args!(1, "a", name: 3)),
+            Err(Error::FormatFailed(_))
+        ));
+
+        // Names are checked whether or not the template has dynamic specs.
+        let args = [Arg::positional(1), Arg::named("a-b", 2)];
+        for source in ["{}", "{:>{}}"] {
+            let template = Template::parse(source).expect("parsing failed");
+            let x = template.render(&args);
+            assert!(matches!(x, Err(Error::InvalidArgName(_))), "{source}");
+        }
+
+        let template: Template = "{0:>{1}} {0:.{p}f}".parse().expect("parsing failed");
+        let x = template.render(&rt_format_args!(1.5, 5, p: 2));
+        assert_eq!(x.expect("formatting failed"), "  1.5 1.50");
+
+        for source in [
+            "{", "}", "{0", "{:{}", "{} {0}", "{0} {}", "{:{0}}", "{a} {}", "{a-b}",
+        ] {
+            assert!(Template::parse(source).is_err(), "{source}");
+        }
+
+        // Parsed once and rendered from several threads.
+        let template = std::sync::Arc::new(Template::parse("{}-{}").expect("parsing failed"));
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let template = template.clone();
+                std::thread::spawn(move || {
+                    template
+                        .render(&rt_format_args!(i, i * 2))
+                        .expect("formatting failed")
+                })
+            })
+            .collect();
+        let x: Vec<_> = threads
+            .into_iter()
+            .map(|t| t.join().expect("thread panicked"))
+            .collect();
+        assert_eq!(x, ["0-0", "1-2", "2-4", "3-6"]);
+    }
+
+    #[test]
+    fn test_template_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<crate::fmt::Template>();
+    }
+}
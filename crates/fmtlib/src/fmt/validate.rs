@@ -0,0 +1,477 @@
+use std::{
+    borrow::Cow,
+    ops::Range,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use super::{
+    errors::{self, FormatError, FormatErrorKind},
+    template::resolve,
+    Arg, ArgRef, Ast, DateTime, NoneStyle, Value, ValueKind,
+};
+
+/// The arguments a template may reference, see [`validate`]. Like with
+/// [`rt_format_args!`](super::rt_format_args), the named arguments come after
+/// the positional ones, and can also be referenced by their index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Signature {
+    pub positional: Vec<ValueKind>,
+    pub named: Vec<(String, ValueKind)>,
+}
+
+impl Signature {
+    /// Returns the signature of `args`.
+    pub fn of(args: &[Arg]) -> Self {
+        let mut signature = Self::default();
+        for arg in args {
+            match arg {
+                Arg::Pos(value) => signature.positional.push(value.kind()),
+                Arg::Named(name, value) => signature.named.push((name.to_string(), value.kind())),
+            }
+        }
+        signature
+    }
+
+    fn len(&self) -> usize {
+        self.positional.len() + self.named.len()
+    }
+
+    /// Returns the index and kind of the argument `arg` refers to.
+    fn find(&self, arg: &ArgRef) -> Option<(usize, ValueKind)> {
+        match arg {
+            ArgRef::Index(index) => self
+                .positional
+                .iter()
+                .chain(self.named.iter().map(|(_, kind)| kind))
+                .nth(*index)
+                .map(|kind| (*index, *kind)),
+            ArgRef::Name(name) => self
+                .named
+                .iter()
+                .position(|(n, _)| n == name)
+                .map(|i| (self.positional.len() + i, self.named[i].1)),
+            ArgRef::Auto => None,
+        }
+    }
+}
+
+/// Checks `template` against the arguments it will be formatted with,
+/// without formatting it, and returns every problem found, in the order of
+/// the template followed by the unused arguments.
+///
+/// ```
+/// # use fmtlib::fmt::{errors::FormatErrorKind, validate, ArgRef, Signature, ValueKind};
+/// let signature = Signature {
+///     positional: vec![ValueKind::String],
+///     named: vec![("count".into(), ValueKind::Int64)],
+/// };
+/// assert!(validate("{} {count:>4}", &signature).is_ok());
+/// let errors = validate("{:x} {user}", &signature).unwrap_err();
+/// assert_eq!(errors.len(), 3);
+/// assert_eq!(errors[1].kind, FormatErrorKind::MissingArgument);
+/// assert_eq!(errors[2].arg, Some(ArgRef::Name("count".into())));
+/// ```
+///
+/// Specs are checked by formatting a placeholder value of the argument's
+/// kind, so they can't be checked for [`ValueKind::Some`],
+/// [`ValueKind::Styled`] and [`ValueKind::Custom`] arguments, and range
+/// specs aren't checked against the elements.
+pub fn validate(template: &str, signature: &Signature) -> Result<(), Vec<FormatError>> {
+    let ast = Ast::parse(template).map_err(|e| vec![into_format_error(e)])?;
+    let errors = check(template, &ast, signature, None);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The error for fmt failing to format `fmt` with `args`. The failure is
+/// located by checking the template in Rust; `message` is fmt's message,
+/// kept if that finds nothing. Arguments that run user code aren't
+/// formatted again, their specs are checked with placeholders.
+pub(crate) fn format_failed(fmt: &str, args: &[Arg], message: String) -> errors::Error {
+    let error = match Ast::parse(fmt) {
+        Ok(ast) => check(fmt, &ast, &Signature::of(args), Some(args))
+            .into_iter()
+            .find(|e| e.kind != FormatErrorKind::UnusedArgument)
+            .unwrap_or_else(|| FormatError::new(FormatErrorKind::Other, message)),
+        Err(e) => into_format_error(e),
+    };
+    errors::Error::FormatFailed(error)
+}
+
+fn into_format_error(e: errors::Error) -> FormatError {
+    match e {
+        errors::Error::InvalidTemplate(e) => e,
+        e => FormatError::new(FormatErrorKind::Other, e.to_string()),
+    }
+}
+
+/// Checks the fields of `ast` against `signature`. Specs are checked with the
+/// values of `args` if given and they don't run user code, and with
+/// placeholder values otherwise.
+fn check(
+    template: &str,
+    ast: &Ast,
+    signature: &Signature,
+    args: Option<&[Arg]>,
+) -> Vec<FormatError> {
+    let mut errors = Vec::new();
+    let mut used = vec![false; signature.len()];
+    // The index of the next `{}`, or None once an index is given.
+    let mut next_index = Some(0);
+
+    let mut find = |arg: ArgRef, span: &Range<usize>, errors: &mut Vec<_>| {
+        let arg = match resolve(arg, &mut next_index) {
+            Ok(arg) => arg,
+            Err(e) => {
+                errors.push(e.with_span(span.clone()));
+                return None;
+            }
+        };
+        match signature.find(&arg) {
+            Some((index, kind)) => {
+                used[index] = true;
+                Some((arg, index, kind))
+            }
+            None => {
+                errors.push(
+                    FormatError::new(
+                        FormatErrorKind::MissingArgument,
+                        format!("argument {arg} not found"),
+                    )
+                    .with_span(span.clone())
+                    .with_arg(arg),
+                );
+                None
+            }
+        }
+    };
+
+    for field in ast.fields() {
+        let found = find(field.arg.clone(), &field.span, &mut errors);
+        let Some(spec) = &field.spec else {
+            continue;
+        };
+        // Widths and precisions are checked with a literal count instead.
+        let mut text = String::new();
+        let mut pos = spec.span.start;
+        for spec_arg in &spec.args {
+            if let Some((arg, _, kind)) = find(spec_arg.arg.clone(), &spec_arg.span, &mut errors) {
+                if !is_integer(kind) {
+                    errors.push(
+                        FormatError::new(
+                            FormatErrorKind::TypeMismatch,
+                            format!("width or precision argument {arg} is not an integer"),
+                        )
+                        .with_span(spec_arg.span.clone())
+                        .with_arg(arg),
+                    );
+                }
+            }
+            text.push_str(&template[pos..spec_arg.span.start]);
+            text.push('1');
+            pos = spec_arg.span.end;
+        }
+        text.push_str(&template[pos..spec.span.end]);
+        let Some((arg, index, kind)) = found else {
+            continue;
+        };
+        let value = args
+            .map(|args| args[index].get_value())
+            .filter(|value| !runs_user_code(value));
+        let result = match value {
+            Some(value) => crate::ffi::fmt::check_spec(value, &text),
+            None => match placeholder(kind) {
+                Some(value) => crate::ffi::fmt::check_spec(&value, &text),
+                None => continue,
+            },
+        };
+        if let Err(e) = result {
+            // A spec some other kind accepts is a mismatch, e.g. `{:%Y}` with
+            // an integer. Custom formatters parse their own specs.
+            let suits_other_kind = kind != ValueKind::Custom
+                && KINDS
+                    .iter()
+                    .filter_map(|&k| placeholder(k))
+                    .any(|value| crate::ffi::fmt::check_spec(&value, &text).is_ok());
+            let error_kind = if suits_other_kind {
+                FormatErrorKind::TypeMismatch
+            } else {
+                FormatErrorKind::InvalidSpec
+            };
+            errors.push(
+                FormatError::new(error_kind, e.to_string())
+                    .with_span(spec.span.clone())
+                    .with_arg(arg),
+            );
+        }
+    }
+
+    for (index, used) in used.into_iter().enumerate() {
+        if !used {
+            let arg = match index.checked_sub(signature.positional.len()) {
+                Some(i) => ArgRef::Name(signature.named[i].0.clone()),
+                None => ArgRef::Index(index),
+            };
+            errors.push(
+                FormatError::new(
+                    FormatErrorKind::UnusedArgument,
+                    format!("argument {arg} is never used"),
+                )
+                .with_arg(arg),
+            );
+        }
+    }
+    errors
+}
+
+fn is_integer(kind: ValueKind) -> bool {
+    matches!(
+        kind,
+        ValueKind::Int64 | ValueKind::Int128 | ValueKind::UInt64 | ValueKind::UInt128
+    )
+}
+
+/// Whether formatting `value` calls a `Format`, `Display` or `Debug`
+/// implementation.
+fn runs_user_code(value: &Value) -> bool {
+    match value {
+        Value::Custom(_) | Value::Display(_) | Value::Debug(_) => true,
+        Value::Some(value, _) | Value::Styled(value, _) => runs_user_code(value),
+        Value::Seq(values) | Value::Tuple(values) => values.iter().any(runs_user_code),
+        Value::Map(entries) => entries
+            .iter()
+            .any(|(k, v)| runs_user_code(k) || runs_user_code(v)),
+        _ => false,
+    }
+}
+
+/// Every [`ValueKind`], to find the kinds a spec suits.
+const KINDS: &[ValueKind] = &[
+    ValueKind::Bool,
+    ValueKind::Char,
+    ValueKind::Float32,
+    ValueKind::Float64,
+    ValueKind::Int64,
+    ValueKind::Int128,
+    ValueKind::UInt64,
+    ValueKind::UInt128,
+    ValueKind::String,
+    ValueKind::None,
+    ValueKind::Some,
+    ValueKind::Seq,
+    ValueKind::Map,
+    ValueKind::Tuple,
+    ValueKind::Pointer,
+    ValueKind::Bytes,
+    ValueKind::Duration,
+    ValueKind::SystemTime,
+    ValueKind::DateTime,
+    ValueKind::Decimal,
+    ValueKind::Styled,
+    ValueKind::Custom,
+    ValueKind::Display,
+    ValueKind::Debug,
+];
+
+/// Returns a value of `kind` to check specs with, if its formatting doesn't
+/// depend on data the kind doesn't describe.
+fn placeholder(kind: ValueKind) -> Option<Value<'static>> {
+    let value = match kind {
+        ValueKind::Bool => Value::Bool(false),
+        ValueKind::Char => Value::Char('a'),
+        ValueKind::Float32 => Value::Float32(0.0),
+        ValueKind::Float64 => Value::Float64(0.0),
+        ValueKind::Int64 => Value::Int64(0),
+        ValueKind::Int128 => Value::Int128(0),
+        ValueKind::UInt64 => Value::UInt64(0),
+        ValueKind::UInt128 => Value::UInt128(0),
+        ValueKind::String => Value::String("".into()),
+        ValueKind::None => Value::None(NoneStyle::default()),
+        ValueKind::Seq => Value::Seq(Vec::new()),
+        ValueKind::Map => Value::Map(Vec::new()),
+        ValueKind::Tuple => Value::Tuple(Vec::new()),
+        ValueKind::Pointer => Value::Pointer(0),
+        ValueKind::Bytes => Value::Bytes(Cow::Borrowed(&[])),
+        ValueKind::Duration => Value::Duration(Duration::ZERO),
+        ValueKind::SystemTime => Value::SystemTime(UNIX_EPOCH),
+        ValueKind::DateTime => Value::DateTime(DateTime::new(0, 0, "UTC")),
+        ValueKind::Decimal => Value::Decimal("0".into()),
+        ValueKind::Display => Value::Display(&""),
+        ValueKind::Debug => Value::Debug(&""),
+        ValueKind::Some | ValueKind::Styled | ValueKind::Custom => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fmt::{rt_format_args, Arg};
+
+    #[test]
+    fn test_validate() {
+        use crate::fmt::errors::{FormatError, FormatErrorKind};
+        use crate::fmt::{validate, ArgRef, Signature, ValueKind};
+
+        let signature = Signature::of(&rt_format_args!("a", 1.5, 2, name: 'c'));
+        assert_eq!(
+            signature.positional,
+            [ValueKind::String, ValueKind::Float64, ValueKind::Int64]
+        );
+        assert_eq!(signature.named, [("name".to_string(), ValueKind::Char)]);
+        assert_eq!(validate("{} {:.{}f} {name:?}", &signature), Ok(()));
+
+        let errors = validate("{0:x} {3:>{0}} {5} {nme} {}", &signature).unwrap_err();
+        let x: Vec<_> = errors
+            .iter()
+            .map(|e| (e.kind, e.span.clone(), e.arg.clone()))
+            .collect();
+        assert_eq!(
+            x,
+            [
+                (
+                    FormatErrorKind::TypeMismatch,
+                    Some(3..4),
+                    Some(ArgRef::Index(0))
+                ),
+                (
+                    FormatErrorKind::TypeMismatch,
+                    Some(10..13),
+                    Some(ArgRef::Index(0))
+                ),
+                (
+                    FormatErrorKind::MissingArgument,
+                    Some(15..18),
+                    Some(ArgRef::Index(5))
+                ),
+                (
+                    FormatErrorKind::MissingArgument,
+                    Some(19..24),
+                    Some(ArgRef::Name("nme".into()))
+                ),
+                (
+                    FormatErrorKind::MixedIndexing,
+                    Some(25..27),
+                    Some(ArgRef::Auto)
+                ),
+                (
+                    FormatErrorKind::UnusedArgument,
+                    None,
+                    Some(ArgRef::Index(1))
+                ),
+                (
+                    FormatErrorKind::UnusedArgument,
+                    None,
+                    Some(ArgRef::Index(2))
+                ),
+            ]
+        );
+        assert_eq!(errors[3].message, "argument nme not found");
+
+        // Specs are mismatched if they suit another kind, and invalid if they
+        // don't suit any.
+        let errors = validate("{2:%Y} {2:q} {2:.{2}}", &signature).unwrap_err();
+        let x: Vec<_> = errors.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            x,
+            [
+                FormatErrorKind::TypeMismatch,
+                FormatErrorKind::InvalidSpec,
+                FormatErrorKind::TypeMismatch,
+                FormatErrorKind::UnusedArgument,
+                FormatErrorKind::UnusedArgument,
+                FormatErrorKind::UnusedArgument,
+            ]
+        );
+
+        let errors = validate("{", &signature).unwrap_err();
+        assert_eq!(
+            errors,
+            [FormatError {
+                kind: FormatErrorKind::UnmatchedBrace,
+                message: "missing '}' in format string".into(),
+                span: Some(0..1),
+                arg: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_format_error_kinds() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use crate::fmt::errors::{Error, FormatErrorKind};
+        use crate::fmt::{format, ArgRef};
+
+        let cases = [
+            (
+                "{} {} {}",
+                FormatErrorKind::MissingArgument,
+                6..8,
+                Some(ArgRef::Index(2)),
+            ),
+            (
+                "{0} {}",
+                FormatErrorKind::MixedIndexing,
+                4..6,
+                Some(ArgRef::Auto),
+            ),
+            (
+                "{:d}",
+                FormatErrorKind::TypeMismatch,
+                2..3,
+                Some(ArgRef::Index(0)),
+            ),
+            (
+                "{:>>>}",
+                FormatErrorKind::InvalidSpec,
+                2..5,
+                Some(ArgRef::Index(0)),
+            ),
+            (
+                "{:{}}",
+                FormatErrorKind::TypeMismatch,
+                2..4,
+                Some(ArgRef::Index(1)),
+            ),
+            ("a}", FormatErrorKind::UnmatchedBrace, 1..2, None),
+            ("{:x", FormatErrorKind::UnmatchedBrace, 0..3, None),
+            ("{a-b}", FormatErrorKind::InvalidArgId, 1..4, None),
+        ];
+        for (fmt, kind, span, arg) in cases {
+            let x = format(fmt, &rt_format_args!("a", "b"));
+            let Err(Error::FormatFailed(e)) = x else {
+                panic!("{fmt}: {x:?}");
+            };
+            assert_eq!((e.kind, e.span, e.arg), (kind, Some(span), arg), "{fmt}");
+        }
+
+        let Err(Error::FormatFailed(e)) = rt_format!("a\né {x}", y: 1) else {
+            panic!()
+        };
+        assert_eq!(
+            e.diagnostic("a\né {x}"),
+            "argument x not found\né {x}\n  ^^^"
+        );
+        assert_eq!(e.diagnostic(""), e.message);
+        assert_eq!(e.to_string(), "argument x not found");
+
+        // Locating the error doesn't format the arguments again.
+        struct Counted<'a>(&'a AtomicUsize);
+        impl std::fmt::Display for Counted<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                f.write_str("a")
+            }
+        }
+        let calls = AtomicUsize::new(0);
+        let counted = Counted(&calls);
+        let Err(Error::FormatFailed(e)) = rt_format!("{:>{}}", %counted, "w") else {
+            panic!()
+        };
+        assert_eq!(e.kind, FormatErrorKind::TypeMismatch);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
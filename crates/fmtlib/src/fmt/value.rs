@@ -1,20 +1,223 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    hash::BuildHasher,
+    ptr::NonNull,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use super::errors;
+use super::{
+    decimal::{self, NumPunct},
+    errors, DateTime, Format, Formatter, Style,
+};
 
-// Only implement types supported by Lua for now
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone)]
 pub enum Value<'s> {
     Bool(bool),
+    Char(char),
+    Float32(f32),
     Float64(f64),
     Int64(i64),
+    Int128(i128),
+    UInt64(u64),
+    UInt128(u128),
     String(Cow<'s, str>),
+    /// A missing optional value, rendered as the style says.
+    None(NoneStyle),
+    Some(Box<Value<'s>>, NoneStyle),
+    Seq(Vec<Value<'s>>),
+    Map(Vec<(Value<'s>, Value<'s>)>),
+    Tuple(Vec<Value<'s>>),
+    Pointer(usize),
+    Bytes(Cow<'s, [u8]>),
+    Duration(Duration),
+    SystemTime(SystemTime),
+    DateTime(DateTime<'s>),
+    /// An exact decimal number, e.g. `-1234.50`, formatted with fmt's
+    /// numeric specs instead of as text. Converted from `rust_decimal` and
+    /// `num-bigint` values with the features of the same name.
+    Decimal(Cow<'s, str>),
+    Styled(Box<Value<'s>>, Style),
+    Custom(&'s (dyn Format + Sync)),
+    Display(&'s (dyn fmt::Display + Sync)),
+    Debug(&'s (dyn fmt::Debug + Sync)),
+}
+
+/// The variant of a [`Value`], without its data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Bool,
+    Char,
+    Float32,
+    Float64,
+    Int64,
+    Int128,
+    UInt64,
+    UInt128,
+    String,
+    None,
+    Some,
+    Seq,
+    Map,
+    Tuple,
+    Pointer,
+    Bytes,
+    Duration,
+    SystemTime,
+    DateTime,
+    Decimal,
+    Styled,
+    Custom,
+    Display,
+    Debug,
+}
+
+impl fmt::Debug for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(v) => f.debug_tuple("Bool").field(v).finish(),
+            Self::Char(v) => f.debug_tuple("Char").field(v).finish(),
+            Self::Float32(v) => f.debug_tuple("Float32").field(v).finish(),
+            Self::Float64(v) => f.debug_tuple("Float64").field(v).finish(),
+            Self::Int64(v) => f.debug_tuple("Int64").field(v).finish(),
+            Self::Int128(v) => f.debug_tuple("Int128").field(v).finish(),
+            Self::UInt64(v) => f.debug_tuple("UInt64").field(v).finish(),
+            Self::UInt128(v) => f.debug_tuple("UInt128").field(v).finish(),
+            Self::String(v) => f.debug_tuple("String").field(v).finish(),
+            Self::None(s) => f.debug_tuple("None").field(s).finish(),
+            Self::Some(v, s) => f.debug_tuple("Some").field(v).field(s).finish(),
+            Self::Seq(v) => f.debug_tuple("Seq").field(v).finish(),
+            Self::Map(v) => f.debug_tuple("Map").field(v).finish(),
+            Self::Tuple(v) => f.debug_tuple("Tuple").field(v).finish(),
+            Self::Pointer(v) => f.debug_tuple("Pointer").field(v).finish(),
+            Self::Bytes(v) => f.debug_tuple("Bytes").field(v).finish(),
+            Self::Duration(v) => f.debug_tuple("Duration").field(v).finish(),
+            Self::SystemTime(v) => f.debug_tuple("SystemTime").field(v).finish(),
+            Self::DateTime(v) => f.debug_tuple("DateTime").field(v).finish(),
+            Self::Decimal(v) => f.debug_tuple("Decimal").field(v).finish(),
+            Self::Styled(v, s) => f.debug_tuple("Styled").field(v).field(s).finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+            Self::Display(v) => f
+                .debug_tuple("Display")
+                .field(&format_args!("{v}"))
+                .finish(),
+            Self::Debug(v) => f.debug_tuple("Debug").field(v).finish(),
+        }
+    }
+}
+
+/// Values borrowing trait objects are only equal if they borrow the same
+/// object.
+impl PartialEq for Value<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        fn same_object<T: ?Sized>(a: &T, b: &T) -> bool {
+            std::ptr::eq(a as *const T as *const (), b as *const T as *const ())
+        }
+
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Char(a), Self::Char(b)) => a == b,
+            (Self::Float32(a), Self::Float32(b)) => a == b,
+            (Self::Float64(a), Self::Float64(b)) => a == b,
+            (Self::Int64(a), Self::Int64(b)) => a == b,
+            (Self::Int128(a), Self::Int128(b)) => a == b,
+            (Self::UInt64(a), Self::UInt64(b)) => a == b,
+            (Self::UInt128(a), Self::UInt128(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::None(a), Self::None(b)) => a == b,
+            (Self::Some(a, sa), Self::Some(b, sb)) => a == b && sa == sb,
+            (Self::Seq(a), Self::Seq(b)) => a == b,
+            (Self::Map(a), Self::Map(b)) => a == b,
+            (Self::Tuple(a), Self::Tuple(b)) => a == b,
+            (Self::Pointer(a), Self::Pointer(b)) => a == b,
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            (Self::Duration(a), Self::Duration(b)) => a == b,
+            (Self::SystemTime(a), Self::SystemTime(b)) => a == b,
+            (Self::DateTime(a), Self::DateTime(b)) => a == b,
+            (Self::Decimal(a), Self::Decimal(b)) => a == b,
+            (Self::Styled(a, s), Self::Styled(b, t)) => a == b && s == t,
+            (Self::Custom(a), Self::Custom(b)) => same_object(*a, *b),
+            (Self::Display(a), Self::Display(b)) => same_object(*a, *b),
+            (Self::Debug(a), Self::Debug(b)) => same_object(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+/// Creates a [`Value::Display`], rendered with `x`'s [`fmt::Display`] impl
+/// when the value is formatted. The `?` presentation is rejected, use
+/// [`debug`] for a debug representation.
+pub fn display<T: fmt::Display + Sync>(x: &T) -> Value<'_> {
+    Value::Display(x)
+}
+
+/// Creates a [`Value::Debug`], rendered with `x`'s [`fmt::Debug`] impl when
+/// the value is formatted.
+pub fn debug<T: fmt::Debug + Sync>(x: &T) -> Value<'_> {
+    Value::Debug(x)
+}
+
+/// Controls how an optional value is rendered, see [`Value::optional`].
+/// Fill, alignment and width apply to the rendered text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum NoneStyle {
+    /// [`Value::None`] renders as an empty string.
+    Empty,
+    /// [`Value::None`] renders as `none`.
+    #[default]
+    None,
+    /// Mirrors fmt's `std::optional` formatter: [`Value::None`] renders as
+    /// `none` and [`Value::Some`] as `optional(...)`. With the other styles,
+    /// a [`Value::Some`] renders as its inner value.
+    Optional,
 }
 
 impl<'val> Value<'val> {
     pub fn new<T: Into<Value<'val>>>(val: T) -> Self {
         val.into()
     }
+
+    /// Creates a [`Value::None`] or [`Value::Some`] rendered with `style`.
+    /// Options converted with `into()` use the default style.
+    ///
+    /// ```
+    /// # use fmtlib::fmt::{rt_format, NoneStyle, Value};
+    /// let x = rt_format!("[{}]", Value::optional(None::<i32>, NoneStyle::Empty));
+    /// assert_eq!(x.unwrap(), "[]");
+    /// ```
+    pub fn optional<T: Into<Value<'val>>>(value: Option<T>, style: NoneStyle) -> Self {
+        match value {
+            Some(v) => Self::Some(Box::new(v.into()), style),
+            None => Self::None(style),
+        }
+    }
+
+    /// Creates a [`Value::Map`] with its entries sorted by key. Use this for
+    /// maps without a stable iteration order, e.g. [`HashMap`], when the
+    /// output has to be reproducible.
+    pub fn sorted_map<K, V, I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Ord + Into<Value<'val>>,
+        V: Into<Value<'val>>,
+    {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+
+    /// Creates a [`Value::Bytes`]. Byte slices and vectors otherwise convert
+    /// into a [`Value::Seq`] of integers.
+    pub fn bytes<B: Into<Cow<'val, [u8]>>>(bytes: B) -> Self {
+        Self::Bytes(bytes.into())
+    }
 }
 
 impl Value<'_> {
@@ -22,10 +225,30 @@ impl Value<'_> {
         matches!(self, Self::Bool(..))
     }
 
+    pub fn is_char(&self) -> bool {
+        matches!(self, Self::Char(..))
+    }
+
     pub fn is_int64(&self) -> bool {
         matches!(self, Self::Int64(..))
     }
 
+    pub fn is_int128(&self) -> bool {
+        matches!(self, Self::Int128(..))
+    }
+
+    pub fn is_uint64(&self) -> bool {
+        matches!(self, Self::UInt64(..))
+    }
+
+    pub fn is_uint128(&self) -> bool {
+        matches!(self, Self::UInt128(..))
+    }
+
+    pub fn is_float32(&self) -> bool {
+        matches!(self, Self::Float32(..))
+    }
+
     pub fn is_float64(&self) -> bool {
         matches!(self, Self::Float64(..))
     }
@@ -34,6 +257,95 @@ impl Value<'_> {
         matches!(self, Self::String(..))
     }
 
+    pub fn is_none(&self) -> bool {
+        matches!(self, Self::None(_))
+    }
+
+    pub fn is_some(&self) -> bool {
+        matches!(self, Self::Some(..))
+    }
+
+    pub fn is_seq(&self) -> bool {
+        matches!(self, Self::Seq(..))
+    }
+
+    pub fn is_map(&self) -> bool {
+        matches!(self, Self::Map(..))
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        matches!(self, Self::Tuple(..))
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, Self::Pointer(..))
+    }
+
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Self::Bytes(..))
+    }
+
+    pub fn is_duration(&self) -> bool {
+        matches!(self, Self::Duration(..))
+    }
+
+    pub fn is_system_time(&self) -> bool {
+        matches!(self, Self::SystemTime(..))
+    }
+
+    pub fn is_datetime(&self) -> bool {
+        matches!(self, Self::DateTime(..))
+    }
+
+    pub fn is_decimal(&self) -> bool {
+        matches!(self, Self::Decimal(..))
+    }
+
+    pub fn is_styled(&self) -> bool {
+        matches!(self, Self::Styled(..))
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(..))
+    }
+
+    pub fn is_display(&self) -> bool {
+        matches!(self, Self::Display(..))
+    }
+
+    pub fn is_debug(&self) -> bool {
+        matches!(self, Self::Debug(..))
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Self::Bool(_) => ValueKind::Bool,
+            Self::Char(_) => ValueKind::Char,
+            Self::Float32(_) => ValueKind::Float32,
+            Self::Float64(_) => ValueKind::Float64,
+            Self::Int64(_) => ValueKind::Int64,
+            Self::Int128(_) => ValueKind::Int128,
+            Self::UInt64(_) => ValueKind::UInt64,
+            Self::UInt128(_) => ValueKind::UInt128,
+            Self::String(_) => ValueKind::String,
+            Self::None(_) => ValueKind::None,
+            Self::Some(..) => ValueKind::Some,
+            Self::Seq(_) => ValueKind::Seq,
+            Self::Map(_) => ValueKind::Map,
+            Self::Tuple(_) => ValueKind::Tuple,
+            Self::Pointer(_) => ValueKind::Pointer,
+            Self::Bytes(_) => ValueKind::Bytes,
+            Self::Duration(_) => ValueKind::Duration,
+            Self::SystemTime(_) => ValueKind::SystemTime,
+            Self::DateTime(_) => ValueKind::DateTime,
+            Self::Decimal(_) => ValueKind::Decimal,
+            Self::Styled(..) => ValueKind::Styled,
+            Self::Custom(_) => ValueKind::Custom,
+            Self::Display(_) => ValueKind::Display,
+            Self::Debug(_) => ValueKind::Debug,
+        }
+    }
+
     pub fn get_bool(&self) -> Result<bool, errors::Error> {
         if let Self::Bool(i) = self {
             Ok(*i)
@@ -42,6 +354,14 @@ impl Value<'_> {
         }
     }
 
+    pub fn get_char(&self) -> Result<char, errors::Error> {
+        if let Self::Char(c) = self {
+            Ok(*c)
+        } else {
+            Err(errors::Error::ValueAccess("char"))
+        }
+    }
+
     pub fn get_int64(&self) -> Result<i64, errors::Error> {
         if let Self::Int64(i) = self {
             Ok(*i)
@@ -50,6 +370,38 @@ impl Value<'_> {
         }
     }
 
+    pub fn get_int128(&self) -> Result<i128, errors::Error> {
+        if let Self::Int128(i) = self {
+            Ok(*i)
+        } else {
+            Err(errors::Error::ValueAccess("int128"))
+        }
+    }
+
+    pub fn get_uint64(&self) -> Result<u64, errors::Error> {
+        if let Self::UInt64(i) = self {
+            Ok(*i)
+        } else {
+            Err(errors::Error::ValueAccess("uint64"))
+        }
+    }
+
+    pub fn get_uint128(&self) -> Result<u128, errors::Error> {
+        if let Self::UInt128(i) = self {
+            Ok(*i)
+        } else {
+            Err(errors::Error::ValueAccess("uint128"))
+        }
+    }
+
+    pub fn get_float32(&self) -> Result<f32, errors::Error> {
+        if let Self::Float32(i) = self {
+            Ok(*i)
+        } else {
+            Err(errors::Error::ValueAccess("float32"))
+        }
+    }
+
     pub fn get_float64(&self) -> Result<f64, errors::Error> {
         if let Self::Float64(i) = self {
             Ok(*i)
@@ -65,6 +417,268 @@ impl Value<'_> {
             Err(errors::Error::ValueAccess("string"))
         }
     }
+
+    pub fn get_some(&self) -> Result<&Value<'_>, errors::Error> {
+        if let Self::Some(v, _) = self {
+            Ok(v)
+        } else {
+            Err(errors::Error::ValueAccess("some"))
+        }
+    }
+
+    pub(crate) fn get_none_style(&self) -> Result<u8, errors::Error> {
+        match self {
+            Self::None(style) | Self::Some(_, style) => Ok(*style as u8),
+            _ => Err(errors::Error::ValueAccess("optional")),
+        }
+    }
+
+    pub fn get_seq(&self) -> Result<&[Value<'_>], errors::Error> {
+        if let Self::Seq(v) = self {
+            Ok(v)
+        } else {
+            Err(errors::Error::ValueAccess("seq"))
+        }
+    }
+
+    pub fn get_map(&self) -> Result<&[(Value<'_>, Value<'_>)], errors::Error> {
+        if let Self::Map(v) = self {
+            Ok(v)
+        } else {
+            Err(errors::Error::ValueAccess("map"))
+        }
+    }
+
+    pub fn get_tuple(&self) -> Result<&[Value<'_>], errors::Error> {
+        if let Self::Tuple(v) = self {
+            Ok(v)
+        } else {
+            Err(errors::Error::ValueAccess("tuple"))
+        }
+    }
+
+    pub fn get_pointer(&self) -> Result<usize, errors::Error> {
+        if let Self::Pointer(p) = self {
+            Ok(*p)
+        } else {
+            Err(errors::Error::ValueAccess("pointer"))
+        }
+    }
+
+    pub fn get_bytes(&self) -> Result<&[u8], errors::Error> {
+        if let Self::Bytes(b) = self {
+            Ok(b)
+        } else {
+            Err(errors::Error::ValueAccess("bytes"))
+        }
+    }
+
+    pub fn get_duration(&self) -> Result<Duration, errors::Error> {
+        if let Self::Duration(d) = self {
+            Ok(*d)
+        } else {
+            Err(errors::Error::ValueAccess("duration"))
+        }
+    }
+
+    pub fn get_system_time(&self) -> Result<SystemTime, errors::Error> {
+        if let Self::SystemTime(t) = self {
+            Ok(*t)
+        } else {
+            Err(errors::Error::ValueAccess("system time"))
+        }
+    }
+
+    pub fn get_datetime(&self) -> Result<&DateTime<'_>, errors::Error> {
+        if let Self::DateTime(t) = self {
+            Ok(t)
+        } else {
+            Err(errors::Error::ValueAccess("datetime"))
+        }
+    }
+
+    pub fn get_decimal(&self) -> Result<&str, errors::Error> {
+        if let Self::Decimal(d) = self {
+            Ok(d)
+        } else {
+            Err(errors::Error::ValueAccess("decimal"))
+        }
+    }
+
+    // The numpunct facet of the format call's locale is passed in for `L`
+    // specs.
+    pub(crate) fn format_decimal(
+        &self,
+        spec: &str,
+        grouping: &[u8],
+        thousands_sep: &str,
+        decimal_point: &str,
+    ) -> Result<String, errors::Error> {
+        let punct = NumPunct {
+            grouping,
+            thousands_sep,
+            decimal_point,
+        };
+        decimal::format_decimal(self.get_decimal()?, spec, &punct)
+    }
+
+    pub fn get_styled(&self) -> Result<(&Value<'_>, &Style), errors::Error> {
+        if let Self::Styled(v, s) = self {
+            Ok((v, s))
+        } else {
+            Err(errors::Error::ValueAccess("styled"))
+        }
+    }
+
+    pub(crate) fn get_styled_value(&self) -> Result<&Value<'_>, errors::Error> {
+        self.get_styled().map(|(v, _)| v)
+    }
+
+    pub(crate) fn get_style(&self) -> Result<&Style, errors::Error> {
+        self.get_styled().map(|(_, s)| s)
+    }
+
+    pub fn get_custom(&self) -> Result<&dyn Format, errors::Error> {
+        if let Self::Custom(c) = self {
+            Ok(*c)
+        } else {
+            Err(errors::Error::ValueAccess("custom"))
+        }
+    }
+
+    pub fn get_display(&self) -> Result<&dyn fmt::Display, errors::Error> {
+        if let Self::Display(d) = self {
+            Ok(*d)
+        } else {
+            Err(errors::Error::ValueAccess("display"))
+        }
+    }
+
+    pub fn get_debug(&self) -> Result<&dyn fmt::Debug, errors::Error> {
+        if let Self::Debug(d) = self {
+            Ok(*d)
+        } else {
+            Err(errors::Error::ValueAccess("debug"))
+        }
+    }
+
+    pub(crate) fn render_display(&self) -> Result<String, errors::Error> {
+        let mut out = String::new();
+        write!(out, "{}", self.get_display()?)?;
+        Ok(out)
+    }
+
+    pub(crate) fn render_debug(&self) -> Result<String, errors::Error> {
+        let mut out = String::new();
+        write!(out, "{:?}", self.get_debug()?)?;
+        Ok(out)
+    }
+
+    pub(crate) fn format_custom(&self, spec: &str) -> Result<String, errors::Error> {
+        let mut out = String::new();
+        self.get_custom()?
+            .format(&mut Formatter::new(spec, &mut out))?;
+        Ok(out)
+    }
+
+    pub(crate) fn get_datetime_timestamp(&self) -> Result<i64, errors::Error> {
+        self.get_datetime().map(|t| t.timestamp)
+    }
+
+    pub(crate) fn get_datetime_offset(&self) -> Result<i32, errors::Error> {
+        self.get_datetime().map(|t| t.offset)
+    }
+
+    pub(crate) fn get_datetime_zone(&self) -> Result<&str, errors::Error> {
+        self.get_datetime().map(|t| t.zone.as_ref())
+    }
+
+    // Durations and time points cross the bridge as a count of the coarsest
+    // unit that represents them exactly, 10^-digits seconds with digits 0, 3,
+    // 6 or 9, since fmt prints as many fractional digits as the unit has.
+
+    pub(crate) fn get_duration_count(&self) -> Result<i64, errors::Error> {
+        let (count, _) = exact_count(self.get_duration()?);
+        i64::try_from(count).map_err(|_| errors::Error::OutOfRange("duration"))
+    }
+
+    pub(crate) fn get_duration_digits(&self) -> Result<u8, errors::Error> {
+        self.get_duration().map(|d| exact_count(d).1)
+    }
+
+    pub(crate) fn get_system_time_count(&self) -> Result<i64, errors::Error> {
+        let count = match self.get_system_time()?.duration_since(UNIX_EPOCH) {
+            Ok(after) => i64::try_from(exact_count(after).0),
+            Err(before) => i64::try_from(exact_count(before.duration()).0).map(|n| -n),
+        };
+        count.map_err(|_| errors::Error::OutOfRange("system time"))
+    }
+
+    pub(crate) fn get_system_time_digits(&self) -> Result<u8, errors::Error> {
+        let since_epoch = match self.get_system_time()?.duration_since(UNIX_EPOCH) {
+            Ok(after) => after,
+            Err(before) => before.duration(),
+        };
+        Ok(exact_count(since_epoch).1)
+    }
+
+    // cxx has no tuple type, so map entries are read one at a time.
+
+    pub(crate) fn get_map_len(&self) -> Result<usize, errors::Error> {
+        self.get_map().map(|m| m.len())
+    }
+
+    pub(crate) fn get_map_key(&self, index: usize) -> Result<&Value<'_>, errors::Error> {
+        self.get_map_entry(index).map(|(k, _)| k)
+    }
+
+    pub(crate) fn get_map_value(&self, index: usize) -> Result<&Value<'_>, errors::Error> {
+        self.get_map_entry(index).map(|(_, v)| v)
+    }
+
+    fn get_map_entry(&self, index: usize) -> Result<&(Value<'_>, Value<'_>), errors::Error> {
+        self.get_map()?
+            .get(index)
+            .ok_or(errors::Error::InvalidOperation("map index out of range"))
+    }
+
+    // cxx has no char type, so chars cross the bridge as their code point.
+    pub(crate) fn get_code_point(&self) -> Result<u32, errors::Error> {
+        self.get_char().map(u32::from)
+    }
+
+    // cxx has no 128-bit integer type, so these values cross the bridge as
+    // their high and low 64-bit halves.
+
+    pub(crate) fn get_int128_high(&self) -> Result<u64, errors::Error> {
+        self.get_int128().map(|i| ((i as u128) >> 64) as u64)
+    }
+
+    pub(crate) fn get_int128_low(&self) -> Result<u64, errors::Error> {
+        self.get_int128().map(|i| i as u128 as u64)
+    }
+
+    pub(crate) fn get_uint128_high(&self) -> Result<u64, errors::Error> {
+        self.get_uint128().map(|i| (i >> 64) as u64)
+    }
+
+    pub(crate) fn get_uint128_low(&self) -> Result<u64, errors::Error> {
+        self.get_uint128().map(|i| i as u64)
+    }
+}
+
+/// Returns `duration` as a count of the coarsest of seconds, milliseconds,
+/// microseconds and nanoseconds that represents it exactly, and that unit's
+/// number of fractional digits.
+fn exact_count(duration: Duration) -> (u128, u8) {
+    let nanos = duration.subsec_nanos();
+    let digits = [0, 3, 6]
+        .into_iter()
+        .find(|digits| nanos % 10u32.pow(9 - digits) == 0)
+        .unwrap_or(9);
+    let count = u128::from(duration.as_secs()) * 10u128.pow(digits)
+        + u128::from(nanos / 10u32.pow(9 - digits));
+    (count, digits as u8)
 }
 
 macro_rules! impl_from_direct {
@@ -77,10 +691,37 @@ macro_rules! impl_from_direct {
     };
 }
 
+macro_rules! impl_from_cast {
+    ($ty:ty, $variant:ident, $target:ty) => {
+        impl<'s> From<$ty> for Value<'s> {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value as $target)
+            }
+        }
+    };
+}
+
 impl_from_direct!(bool, Bool);
+impl_from_direct!(char, Char);
 impl_from_direct!(i64, Int64);
+impl_from_direct!(i128, Int128);
+impl_from_direct!(u64, UInt64);
+impl_from_direct!(u128, UInt128);
+impl_from_direct!(f32, Float32);
 impl_from_direct!(f64, Float64);
 impl_from_direct!(Cow<'s, str>, String);
+impl_from_direct!(Cow<'s, [u8]>, Bytes);
+impl_from_direct!(Duration, Duration);
+impl_from_direct!(SystemTime, SystemTime);
+
+impl_from_cast!(i8, Int64, i64);
+impl_from_cast!(i16, Int64, i64);
+impl_from_cast!(i32, Int64, i64);
+impl_from_cast!(isize, Int64, i64);
+impl_from_cast!(u8, UInt64, u64);
+impl_from_cast!(u16, UInt64, u64);
+impl_from_cast!(u32, UInt64, u64);
+impl_from_cast!(usize, UInt64, u64);
 
 impl<'s> From<&'s str> for Value<'s> {
     fn from(value: &'s str) -> Self {
@@ -88,8 +729,173 @@ impl<'s> From<&'s str> for Value<'s> {
     }
 }
 
+impl<'s> From<&'s (dyn fmt::Display + Sync)> for Value<'s> {
+    fn from(value: &'s (dyn fmt::Display + Sync)) -> Self {
+        Value::Display(value)
+    }
+}
+
+impl<'s> From<&'s (dyn fmt::Debug + Sync)> for Value<'s> {
+    fn from(value: &'s (dyn fmt::Debug + Sync)) -> Self {
+        Value::Debug(value)
+    }
+}
+
 impl<'s> From<String> for Value<'s> {
     fn from(value: String) -> Self {
         Value::String(Cow::Owned(value))
     }
 }
+
+impl<'s, T: ?Sized> From<*const T> for Value<'s> {
+    fn from(value: *const T) -> Self {
+        Value::Pointer(value.cast::<()>() as usize)
+    }
+}
+
+impl<'s, T: ?Sized> From<*mut T> for Value<'s> {
+    fn from(value: *mut T) -> Self {
+        Value::Pointer(value.cast::<()>() as usize)
+    }
+}
+
+impl<'s, T: ?Sized> From<NonNull<T>> for Value<'s> {
+    fn from(value: NonNull<T>) -> Self {
+        value.as_ptr().into()
+    }
+}
+
+impl<'s, T> From<Option<T>> for Value<'s>
+where
+    T: Into<Value<'s>>,
+{
+    fn from(value: Option<T>) -> Self {
+        Value::optional(value, NoneStyle::default())
+    }
+}
+
+impl<'s, T> From<Vec<T>> for Value<'s>
+where
+    T: Into<Value<'s>>,
+{
+    fn from(value: Vec<T>) -> Self {
+        Value::Seq(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'s, T> From<&'s [T]> for Value<'s>
+where
+    T: Into<Value<'s>> + Clone,
+{
+    fn from(value: &'s [T]) -> Self {
+        Value::Seq(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<'s, T, const N: usize> From<[T; N]> for Value<'s>
+where
+    T: Into<Value<'s>>,
+{
+    fn from(value: [T; N]) -> Self {
+        Value::Seq(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'s, T, const N: usize> From<&'s [T; N]> for Value<'s>
+where
+    T: Into<Value<'s>> + Clone,
+{
+    fn from(value: &'s [T; N]) -> Self {
+        value[..].into()
+    }
+}
+
+impl<'s, K, V> From<BTreeMap<K, V>> for Value<'s>
+where
+    K: Into<Value<'s>>,
+    V: Into<Value<'s>>,
+{
+    fn from(value: BTreeMap<K, V>) -> Self {
+        Value::Map(
+            value
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Entries keep the map's iteration order, which isn't stable for
+/// [`HashMap`]. Use [`Value::sorted_map`] for reproducible output.
+impl<'s, K, V, S> From<HashMap<K, V, S>> for Value<'s>
+where
+    K: Into<Value<'s>>,
+    V: Into<Value<'s>>,
+    S: BuildHasher,
+{
+    fn from(value: HashMap<K, V, S>) -> Self {
+        Value::Map(
+            value
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<'s, K, V, S> From<indexmap::IndexMap<K, V, S>> for Value<'s>
+where
+    K: Into<Value<'s>>,
+    V: Into<Value<'s>>,
+{
+    fn from(value: indexmap::IndexMap<K, V, S>) -> Self {
+        Value::Map(
+            value
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+macro_rules! impl_from_tuple {
+    ($($ty:ident),+) => {
+        impl<'s, $($ty),+> From<($($ty,)+)> for Value<'s>
+        where
+            $($ty: Into<Value<'s>>),+
+        {
+            #[allow(non_snake_case)]
+            fn from(value: ($($ty,)+)) -> Self {
+                let ($($ty,)+) = value;
+                Value::Tuple(vec![$($ty.into()),+])
+            }
+        }
+    };
+}
+
+impl_from_tuple!(A);
+impl_from_tuple!(A, B);
+impl_from_tuple!(A, B, C);
+impl_from_tuple!(A, B, C, D);
+impl_from_tuple!(A, B, C, D, E);
+impl_from_tuple!(A, B, C, D, E, F);
+impl_from_tuple!(A, B, C, D, E, F, G);
+impl_from_tuple!(A, B, C, D, E, F, G, H);
+impl_from_tuple!(A, B, C, D, E, F, G, H, I);
+impl_from_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_from_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_from_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fmt::Arg;
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Value>();
+        assert_send_sync::<Arg>();
+    }
+}
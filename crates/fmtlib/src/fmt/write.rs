@@ -0,0 +1,311 @@
+use std::{fmt, io};
+
+use super::{errors, format_failed, validate_names, Arg};
+
+/// Formats `fmt` with `args` into `out`, e.g. appending to a `String`. The
+/// output is passed to `out` in chunks as it's formatted.
+///
+/// ```
+/// # use fmtlib::fmt::{format_to, rt_format_args, Arg};
+/// let mut out = String::from("total: ");
+/// format_to(&mut out, "{:.2f}", &rt_format_args!(12.5)).unwrap();
+/// assert_eq!(out, "total: 12.50");
+/// ```
+///
+/// Output written before an error is kept in `out`.
+pub fn format_to<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    fmt: &str,
+    args: &[Arg],
+) -> Result<(), errors::Error> {
+    let mut out = out;
+    Writer::new(Sink::Fmt(&mut out)).format(fmt, args)
+}
+
+/// Formats `fmt` with `args` into `out`, e.g. a file or a socket. The output
+/// is passed to `out` in chunks as it's formatted.
+pub fn format_to_io<W: io::Write + ?Sized>(
+    out: &mut W,
+    fmt: &str,
+    args: &[Arg],
+) -> Result<(), errors::Error> {
+    let mut out = out;
+    Writer::new(Sink::Io(&mut out)).format(fmt, args)
+}
+
+/// The result of [`format_to_n`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatToN {
+    /// The number of bytes written to the buffer.
+    pub written: usize,
+    /// The size of the untruncated output.
+    pub size: usize,
+}
+
+impl FormatToN {
+    pub fn is_truncated(&self) -> bool {
+        self.size > self.written
+    }
+}
+
+/// Formats `fmt` with `args` into `buf`, writing at most `buf.len()` bytes,
+/// like fmt's `format_to_n`.
+///
+/// ```
+/// # use fmtlib::fmt::{format_to_n, rt_format_args, Arg};
+/// let mut buf = [0; 4];
+/// let result = format_to_n(&mut buf, "{}", &rt_format_args!(123456)).unwrap();
+/// assert_eq!((result.written, result.size), (4, 6));
+/// assert_eq!(&buf, b"1234");
+/// ```
+///
+/// The output is cut at a byte boundary, so a truncated output may end with
+/// an incomplete UTF-8 sequence. See [`format_truncated`] for a string that's
+/// cut at a char boundary.
+pub fn format_to_n(buf: &mut [u8], fmt: &str, args: &[Arg]) -> Result<FormatToN, errors::Error> {
+    validate_names(args)?;
+    let size = crate::ffi::fmt::format_to_n(buf, fmt, args)
+        .map_err(|e| format_failed(fmt, args, e.to_string()))?;
+    Ok(FormatToN {
+        written: size.min(buf.len()),
+        size,
+    })
+}
+
+/// Formats `fmt` with `args` into a string of at most `max_len` bytes. If the
+/// output is longer, it's cut at a char boundary and `ellipsis` is appended,
+/// within `max_len`.
+///
+/// ```
+/// # use fmtlib::fmt::{format_truncated, rt_format_args, Arg};
+/// let args = rt_format_args!("world");
+/// let text = format_truncated(10, Some("…"), "hello {}!", &args).unwrap();
+/// assert_eq!(text, "hello w…");
+/// ```
+///
+/// Fails if the ellipsis is longer than `max_len`.
+pub fn format_truncated(
+    max_len: usize,
+    ellipsis: Option<&str>,
+    fmt: &str,
+    args: &[Arg],
+) -> Result<String, errors::Error> {
+    let ellipsis = ellipsis.unwrap_or("");
+    if ellipsis.len() > max_len {
+        return Err(errors::Error::InvalidOperation(
+            "ellipsis is longer than the maximum length",
+        ));
+    }
+    let mut buf = vec![0; max_len];
+    let result = format_to_n(&mut buf, fmt, args)?;
+    buf.truncate(result.written);
+    if result.is_truncated() {
+        buf.truncate(max_len - ellipsis.len());
+        // Drop the start of a char that doesn't fit.
+        if let Err(e) = std::str::from_utf8(&buf) {
+            buf.truncate(e.valid_up_to());
+        }
+        buf.extend_from_slice(ellipsis.as_bytes());
+    }
+    String::from_utf8(buf)
+        .map_err(|_| errors::Error::InvalidOperation("formatted output is not valid UTF-8"))
+}
+
+enum Sink<'a> {
+    Fmt(&'a mut dyn fmt::Write),
+    Io(&'a mut dyn io::Write),
+}
+
+/// Receives the output of a format call from C++.
+#[doc(hidden)]
+pub struct Writer<'a> {
+    sink: Sink<'a>,
+    /// The start of a UTF-8 sequence split across chunks.
+    partial: Vec<u8>,
+    /// The sink's error. Only its message crosses the bridge, so it's kept to
+    /// be returned instead of the error thrown by fmt.
+    error: Option<errors::Error>,
+}
+
+impl<'a> Writer<'a> {
+    fn new(sink: Sink<'a>) -> Self {
+        Self {
+            sink,
+            partial: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn format(mut self, fmt: &str, args: &[Arg]) -> Result<(), errors::Error> {
+        validate_names(args)?;
+        let result = crate::ffi::fmt::format_to(&mut self, fmt, args);
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        result.map_err(|e| format_failed(fmt, args, e.to_string()))?;
+        if !self.partial.is_empty() {
+            return Err(errors::Error::InvalidOperation(
+                "formatted output is not valid UTF-8",
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write(&mut self, chunk: &[u8]) -> Result<(), errors::Error> {
+        let result = match &mut self.sink {
+            Sink::Fmt(out) => write_utf8(*out, &mut self.partial, chunk),
+            Sink::Io(out) => out.write_all(chunk).map_err(errors::Error::from),
+        };
+        result.map_err(|e| {
+            self.error = Some(e);
+            errors::Error::InvalidOperation("failed to write formatted output")
+        })
+    }
+}
+
+fn write_utf8(
+    out: &mut dyn fmt::Write,
+    partial: &mut Vec<u8>,
+    chunk: &[u8],
+) -> Result<(), errors::Error> {
+    let mut buf = std::mem::take(partial);
+    let bytes = if buf.is_empty() {
+        chunk
+    } else {
+        buf.extend_from_slice(chunk);
+        &buf
+    };
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        // Only the end of the chunk may be an incomplete sequence.
+        Err(e) if e.error_len().is_none() => {
+            partial.extend_from_slice(&bytes[e.valid_up_to()..]);
+            // Checked by from_utf8.
+            std::str::from_utf8(&bytes[..e.valid_up_to()]).expect("valid prefix")
+        }
+        Err(_) => {
+            return Err(errors::Error::InvalidOperation(
+                "formatted output is not valid UTF-8",
+            ))
+        }
+    };
+    out.write_str(valid)?;
+    Ok(())
+}
+
+// The rt_write! and rt_writeln! macros call `rt_write` on their destination,
+// which resolves to one of these traits, depending on which kind of writer
+// it is.
+
+#[doc(hidden)]
+pub trait FmtWrite {
+    fn rt_write(&mut self, fmt: &str, args: &[Arg]) -> Result<(), errors::Error>;
+}
+
+impl<W: fmt::Write + ?Sized> FmtWrite for W {
+    fn rt_write(&mut self, fmt: &str, args: &[Arg]) -> Result<(), errors::Error> {
+        format_to(self, fmt, args)
+    }
+}
+
+#[doc(hidden)]
+pub trait IoWrite {
+    fn rt_write(&mut self, fmt: &str, args: &[Arg]) -> Result<(), errors::Error>;
+}
+
+impl<W: io::Write + ?Sized> IoWrite for W {
+    fn rt_write(&mut self, fmt: &str, args: &[Arg]) -> Result<(), errors::Error> {
+        format_to_io(self, fmt, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fmt::rt_format_args;
+
+    #[test]
+    fn test_write() {
+        use crate::fmt::{errors::Error, format_to, format_to_io, Arg};
+
+        let mut s = String::from(">");
+        rt_write!(s, "{} {a}", 1, a: "é").expect("formatting failed");
+        rt_writeln!(&mut s, "{:x}", 255).expect("formatting failed");
+        rt_writeln!(s).expect("formatting failed");
+        assert_eq!(s, ">1 éff\n\n");
+
+        let mut bytes = Vec::new();
+        rt_writeln!(bytes, "{:-^3}", 'x').expect("formatting failed");
+        assert_eq!(bytes, b"-x-\n");
+        let template = String::from("{}|{}");
+        rt_writeln!(bytes, template, 1, 2).expect("formatting failed");
+        assert_eq!(bytes, b"-x-\n1|2\n");
+
+        // Larger than the chunks output is flushed in.
+        let long = "é".repeat(10000);
+        let args = [
+            Arg::positional(long.as_str()),
+            Arg::positional(long.as_str()),
+        ];
+        let mut s = String::new();
+        format_to(&mut s, "{}{}", &args).expect("formatting failed");
+        assert_eq!(s, long.repeat(2));
+        let mut bytes = Vec::new();
+        format_to_io(&mut bytes, "{}", &args).expect("formatting failed");
+        assert_eq!(bytes, long.as_bytes());
+
+        struct Full;
+        impl std::io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::WriteZero.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let x = rt_write!(Full, "{}", 1);
+        assert!(matches!(
+            x,
+            Err(Error::Io {
+                kind: std::io::ErrorKind::WriteZero,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_format_to_n() {
+        use crate::fmt::{format_to_n, format_truncated, Arg, FormatToN};
+
+        let mut buf = [0; 8];
+        let x = format_to_n(&mut buf, "{:>4}|{}", &rt_format_args!(1, "abcdef"));
+        assert_eq!(
+            x.expect("formatting failed"),
+            FormatToN {
+                written: 8,
+                size: 11
+            }
+        );
+        assert_eq!(&buf, b"   1|abc");
+        let x = format_to_n(&mut buf, "{}", &rt_format_args!(12));
+        assert_eq!(
+            x.expect("formatting failed"),
+            FormatToN {
+                written: 2,
+                size: 2
+            }
+        );
+        assert!(format_to_n(&mut buf, "{:d}", &rt_format_args!("a")).is_err());
+
+        let x = format_truncated(8, Some("..."), "{} {}", &rt_format_args!("hello", "world"));
+        assert_eq!(x.expect("formatting failed"), "hello...");
+        let x = format_truncated(11, Some("..."), "{} {}", &rt_format_args!("hello", "world"));
+        assert_eq!(x.expect("formatting failed"), "hello world");
+        // 'é' is 2 bytes long, so only two fit.
+        let x = format_truncated(5, None, "{}", &rt_format_args!("ééé"));
+        assert_eq!(x.expect("formatting failed"), "éé");
+        let x = format_truncated(5, Some("…"), "{}", &rt_format_args!("ééé"));
+        assert_eq!(x.expect("formatting failed"), "é…");
+        assert!(format_truncated(2, Some("..."), "", &[]).is_err());
+    }
+}